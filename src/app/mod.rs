@@ -1,16 +1,32 @@
-use ggez::{event::{self, MouseButton}, glam::Vec2, graphics, Context, GameResult};
+use ggez::{event::{self, MouseButton}, glam::Vec2, graphics, input::keyboard::{KeyCode, KeyInput}, Context, GameResult};
 
-use crate::{assets::Assets, game::Game, scenes::{Scene, main_menu::MainMenu}};
+use crate::{assets::Assets, game::{game_config::GameConfig, saved_game::SavedGame, Game}, network::NetSession, scenes::{Scene, join_menu::JoinMenu, load_menu::LoadMenu, main_menu::MainMenu, new_game_menu::NewGameMenu, position_editor::PositionEditor, puzzle_trainer::PuzzleTrainer, settings_menu::SettingsMenu}, settings::Settings, sounds::{SoundEffect, Sounds}};
 
 pub enum AppEvent {
     OpenMainMenu,
     OpenNewGame,
+    StartGame(GameConfig),
+    JoinGame(String),
+    StartNetworkGame(SavedGame, Box<NetSession>),
+    SetUpPosition(String),
+    OpenEditor,
+    OpenPuzzles,
+    OpenSettings,
+    OpenLoadMenu,
+    OpenSavedGame(SavedGame),
+    UpdateSettings(Settings),
+    PlaySound(SoundEffect),
 }
 
 pub struct App {
+    pub settings: Settings,
     pub assets: Assets,
+    pub sounds: Sounds,
     pub current_scene: Box<dyn Scene>,
     pub press_data: Option<(Vec2, MouseButton)>,
+    pub release_data: Option<(Vec2, MouseButton)>,
+    pub key_data: Option<KeyCode>,
+    pub text_input: String,
     pub events: Vec<AppEvent>,
     // pub press_button: Option<MouseButton>,
     // pub highlighted_spaces: Vec<[usize; 2]>
@@ -18,10 +34,21 @@ pub struct App {
 
 impl App {
     pub fn init(ctx: &mut Context) -> Self {
+        let settings = Settings::load();
+
+        let mut sounds = Sounds::load_all(ctx);
+        sounds.volume = settings.volume;
+        sounds.muted = settings.muted;
+
         Self {
-            assets: Assets::load_all(ctx),
+            assets: Assets::load_theme(ctx, &settings.theme),
+            sounds,
+            settings,
             current_scene: Box::new(MainMenu::new(ctx)),
             press_data: None,
+            release_data: None,
+            key_data: None,
+            text_input: String::new(),
             events: Vec::new(),
             // press_button: None,
             // highlighted_spaces: Vec::new()
@@ -34,13 +61,56 @@ impl event::EventHandler<ggez::GameError> for App {
         for event in self.events.drain(..) {
             match event {
                 AppEvent::OpenMainMenu => self.current_scene = Box::new(MainMenu::new(ctx)),
-                AppEvent::OpenNewGame => self.current_scene = Box::new(Game::new()),
+                AppEvent::OpenNewGame => self.current_scene = Box::new(NewGameMenu::new(ctx, &self.settings, None)),
+                AppEvent::StartGame(config) => {
+                    self.current_scene = match Game::new(ctx, &self.settings, config) {
+                        Ok(game) => Box::new(game),
+                        Err(error) => Box::new(NewGameMenu::new(ctx, &self.settings, Some(format!("Could not start game: {}", error)))),
+                    };
+                },
+                AppEvent::JoinGame(address) => self.current_scene = Box::new(JoinMenu::new(ctx, address)),
+                AppEvent::StartNetworkGame(saved_game, network) => {
+                    self.current_scene = match Game::from_saved_game(ctx, &self.settings, &saved_game) {
+                        Ok(mut game) => {
+                            game.attach_network(*network, ctx);
+
+                            Box::new(game)
+                        },
+                        Err(error) => Box::new(NewGameMenu::new(ctx, &self.settings, Some(format!("Could not join game: {}", error)))),
+                    };
+                },
+                AppEvent::SetUpPosition(fen) => self.current_scene = Box::new(NewGameMenu::with_fen(ctx, &self.settings, fen)),
+                AppEvent::OpenEditor => self.current_scene = Box::new(PositionEditor::new(ctx)),
+                AppEvent::OpenPuzzles => self.current_scene = Box::new(PuzzleTrainer::new(ctx, &self.settings)),
+                AppEvent::OpenLoadMenu => self.current_scene = Box::new(LoadMenu::new(ctx, None)),
+                AppEvent::OpenSavedGame(saved_game) => {
+                    self.current_scene = match Game::from_saved_game(ctx, &self.settings, &saved_game) {
+                        Ok(game) => Box::new(game),
+                        Err(error) => Box::new(LoadMenu::new(ctx, Some(format!("Could not load game: {}", error)))),
+                    };
+                },
+                AppEvent::OpenSettings => self.current_scene = Box::new(SettingsMenu::new(ctx, self.settings.clone())),
+                AppEvent::UpdateSettings(settings) => {
+                    if settings.theme != self.assets.theme {
+                        self.assets = Assets::load_theme(ctx, &settings.theme);
+                    }
+
+                    self.sounds.volume = settings.volume;
+                    self.sounds.muted = settings.muted;
+
+                    settings.save();
+                    self.settings = settings;
+                },
+                AppEvent::PlaySound(effect) => self.sounds.play(ctx, effect),
             }   
         }
 
-        self.current_scene.update(self.press_data, ctx, &mut self.events);
+        self.current_scene.update(self.press_data, self.release_data, self.key_data, &self.text_input, ctx, &mut self.events);
 
         self.press_data = None;
+        self.release_data = None;
+        self.key_data = None;
+        self.text_input.clear();
 
         Ok(())
     }
@@ -60,6 +130,24 @@ impl event::EventHandler<ggez::GameError> for App {
         // self.press_button = Some(button);
 
         
+        Ok(())
+    }
+
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: event::MouseButton, x: f32, y: f32,) -> GameResult {
+        self.release_data = Some((Vec2::from([x, y]), button));
+
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, _ctx: &mut Context, input: KeyInput, _repeated: bool) -> GameResult {
+        self.key_data = input.keycode;
+
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        self.text_input.push(character);
+
         Ok(())
     }
 }
\ No newline at end of file
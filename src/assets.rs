@@ -1,43 +1,190 @@
+use std::io::Read;
+
+use ggez::graphics::{Color, ImageFormat};
+use serde::Deserialize;
+
 use crate::*;
 
+pub const DEFAULT_THEME: &str = "classic";
+
+const DEFAULT_BORDER_COLOR: [u8; 3] = [87, 114, 119];
+const DEFAULT_LABEL_COLOR: [u8; 3] = [235, 237, 233];
+
+const HIGHLIGHT_ASSETS: [(&str, &str); 7] = [
+    ("whiteselect", "/Selected_White.png"),
+    ("blackselect", "/Selected_Black.png"),
+    ("whitehighlight", "/Highlight_White.png"),
+    ("blackhighlight", "/Highlight_Black.png"),
+    ("movehighlight", "/Move_Highlight.png"),
+    ("capturehighlight", "/Capture_Highlight.png"),
+    ("checkhighlight", "/Check_Highlight.png"),
+];
+
+const PIECE_ASSETS: [(&str, &str); 6] = [
+    ("pawn", "Pawn.png"),
+    ("knight", "Knight.png"),
+    ("bishop", "Bishop.png"),
+    ("rook", "Rook.png"),
+    ("queen", "Queen.png"),
+    ("king", "King.png"),
+];
+
+#[derive(Deserialize, Default)]
+pub struct ThemeManifest {
+    pub name: Option<String>,
+    pub pieces: Option<String>,
+    pub board: Option<String>,
+    pub board_colors: Option<BoardColors>,
+    #[serde(default)]
+    pub highlights: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct BoardColors {
+    pub light: [u8; 3],
+    pub dark: [u8; 3],
+    pub border: Option<[u8; 3]>,
+    pub label: Option<[u8; 3]>,
+}
+
 pub struct Assets {
-    pub graphics: HashMap<String, Image>
+    pub graphics: HashMap<String, Image>,
+    pub theme: String,
+    pub theme_name: String,
+    pub border_color: Color,
+    pub label_color: Color,
+    pub errors: Vec<String>,
 }
 
 impl Assets {
-    pub fn load_all(ctx: &mut Context) -> Self {
-        let mut graphics: HashMap<String, Image> = HashMap::new();
-        graphics.insert("board".into(), Image::from_path(ctx, "/Board/Board.png").unwrap());
+    pub fn load_theme(ctx: &mut Context, theme: &str) -> Self {
+        let mut errors = Vec::new();
+
+        let manifest = match Self::read_manifest(ctx, theme) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                if theme != DEFAULT_THEME {
+                    errors.push(format!("Theme '{}': {}, using '{}'", theme, error, DEFAULT_THEME));
+
+                    let mut assets = Self::load_theme(ctx, DEFAULT_THEME);
+                    errors.append(&mut assets.errors);
+                    assets.errors = errors;
+
+                    return assets;
+                }
+
+                ThemeManifest::default()
+            }
+        };
 
-        graphics.insert("whiteselect".into(), Image::from_path(ctx, "/Selected_White.png").unwrap());
-        graphics.insert("blackselect".into(), Image::from_path(ctx, "/Selected_Black.png").unwrap());
+        let theme_dir = format!("/themes/{}", theme);
+        let resolve = |path: &str| -> String {
+            if path.starts_with('/') {
+                return path.into();
+            }
 
-        graphics.insert("whitehighlight".into(), Image::from_path(ctx, "/Highlight_White.png").unwrap());
-        graphics.insert("blackhighlight".into(), Image::from_path(ctx, "/Highlight_Black.png").unwrap());
+            format!("{}/{}", theme_dir, path)
+        };
 
-        graphics.insert("movehighlight".into(), Image::from_path(ctx, "/Move_Highlight.png").unwrap());
-        graphics.insert("capturehighlight".into(), Image::from_path(ctx, "/Capture_Highlight.png").unwrap());
-        graphics.insert("checkhighlight".into(), Image::from_path(ctx, "/Check_Highlight.png").unwrap());
+        let mut graphics: HashMap<String, Image> = HashMap::new();
+
+        let board_colors = manifest.board_colors.as_ref();
 
+        let board = match (&manifest.board, board_colors) {
+            (Some(path), _) => Self::load_image(ctx, &resolve(path), "/Board/Board.png", &mut errors),
+            (None, Some(colors)) => Self::generate_board(ctx, colors),
+            (None, None) => Self::load_image(ctx, "/Board/Board.png", "/Board/Board.png", &mut errors),
+        };
+        graphics.insert("board".into(), board);
 
-        graphics.insert("blackpawn".into(), Image::from_path(ctx, "/Pieces/BlackPieces/Pawn.png").unwrap());
-        graphics.insert("blackknight".into(), Image::from_path(ctx, "/Pieces/BlackPieces/Knight.png").unwrap());
-        graphics.insert("blackbishop".into(), Image::from_path(ctx, "/Pieces/BlackPieces/Bishop.png").unwrap());
-        graphics.insert("blackrook".into(), Image::from_path(ctx, "/Pieces/BlackPieces/Rook.png").unwrap());
-        graphics.insert("blackqueen".into(), Image::from_path(ctx, "/Pieces/BlackPieces/Queen.png").unwrap());
-        graphics.insert("blackking".into(), Image::from_path(ctx, "/Pieces/BlackPieces/King.png").unwrap());
+        for (id, default_path) in HIGHLIGHT_ASSETS {
+            let path = manifest.highlights.get(id).map(|path| resolve(path)).unwrap_or(default_path.into());
 
-        graphics.insert("whitepawn".into(), Image::from_path(ctx, "/Pieces/WhitePieces/Pawn.png").unwrap());
-        graphics.insert("whiteknight".into(), Image::from_path(ctx, "/Pieces/WhitePieces/Knight.png").unwrap());
-        graphics.insert("whitebishop".into(), Image::from_path(ctx, "/Pieces/WhitePieces/Bishop.png").unwrap());
-        graphics.insert("whiterook".into(), Image::from_path(ctx, "/Pieces/WhitePieces/Rook.png").unwrap());
-        graphics.insert("whitequeen".into(), Image::from_path(ctx, "/Pieces/WhitePieces/Queen.png").unwrap());
-        graphics.insert("whiteking".into(), Image::from_path(ctx, "/Pieces/WhitePieces/King.png").unwrap());
+            graphics.insert(id.into(), Self::load_image(ctx, &path, default_path, &mut errors));
+        }
 
+        let pieces_dir = manifest.pieces.as_deref().map(resolve).unwrap_or("/Pieces".into());
 
+        for (player, player_dir) in [("black", "BlackPieces"), ("white", "WhitePieces")] {
+            for (piece, file) in PIECE_ASSETS {
+                let path = format!("{}/{}/{}", pieces_dir, player_dir, file);
+                let default_path = format!("/Pieces/{}/{}", player_dir, file);
+
+                graphics.insert(String::from(player) + piece, Self::load_image(ctx, &path, &default_path, &mut errors));
+            }
+        }
 
         Self {
-            graphics
+            graphics,
+            theme: theme.into(),
+            theme_name: manifest.name.unwrap_or(theme.into()),
+            border_color: Self::to_color(board_colors.and_then(|colors| colors.border).unwrap_or(DEFAULT_BORDER_COLOR)),
+            label_color: Self::to_color(board_colors.and_then(|colors| colors.label).unwrap_or(DEFAULT_LABEL_COLOR)),
+            errors,
         }
-    } 
-}
\ No newline at end of file
+    }
+
+    pub fn get_theme_names(ctx: &Context) -> Vec<String> {
+        let mut themes: Vec<String> = ctx.fs.read_dir("/themes")
+            .map(|entries| entries
+                .filter(|path| ctx.fs.is_dir(path))
+                .filter_map(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+                .collect())
+            .unwrap_or_default();
+
+        if !themes.iter().any(|theme| theme == DEFAULT_THEME) {
+            themes.push(DEFAULT_THEME.into());
+        }
+
+        themes.sort();
+
+        themes
+    }
+
+    fn read_manifest(ctx: &Context, theme: &str) -> Result<ThemeManifest, String> {
+        let mut file = ctx.fs.open(format!("/themes/{}/theme.toml", theme))
+            .map_err(|_| String::from("missing theme.toml"))?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).map_err(|error| error.to_string())?;
+
+        toml::from_str(&contents).map_err(|error| format!("invalid theme.toml: {}", error))
+    }
+
+    fn to_color(rgb: [u8; 3]) -> Color {
+        Color::from_rgb(rgb[0], rgb[1], rgb[2])
+    }
+
+    fn load_image(ctx: &mut Context, path: &str, default_path: &str, errors: &mut Vec<String>) -> Image {
+        if let Ok(image) = Image::from_path(ctx, path) {
+            return image;
+        }
+
+        errors.push(format!("Could not load '{}', using '{}'", path, default_path));
+
+        Image::from_path(ctx, default_path).unwrap_or_else(|_| {
+            errors.push(format!("Could not load '{}'", default_path));
+
+            Image::from_color(ctx, 32, 32, Some(Color::MAGENTA))
+        })
+    }
+
+    fn generate_board(ctx: &mut Context, colors: &BoardColors) -> Image {
+        let border = colors.border.unwrap_or(DEFAULT_BORDER_COLOR);
+        let mut pixels = Vec::with_capacity(288 * 288 * 4);
+
+        for y in 0..288 {
+            for x in 0..288 {
+                let color = if (16..272).contains(&x) && (16..272).contains(&y) {
+                    if ((x - 16) / 32 + (y - 16) / 32) % 2 == 0 { colors.light } else { colors.dark }
+                } else {
+                    border
+                };
+
+                pixels.extend_from_slice(&[color[0], color[1], color[2], 255]);
+            }
+        }
+
+        Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8UnormSrgb, 288, 288)
+    }
+}
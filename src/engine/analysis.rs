@@ -0,0 +1,310 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver}, Arc}, thread};
+
+use crate::game::{board::Board, piece::Player};
+
+use super::{search::{Search, SearchInfo, SearchLimits}, transposition::TranspositionTable, uci_engine::{self, UciEngine}};
+
+pub const ANALYSIS_LINES: usize = 3;
+
+const MAX_ANALYSIS_DEPTH: u8 = 32;
+
+// scores are always from white's point of view
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Score {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+impl Score {
+    pub fn for_player(self, player: Player) -> Self {
+        if player == Player::White {
+            return self;
+        }
+
+        match self {
+            Score::Centipawns(centipawns) => Score::Centipawns(-centipawns),
+            Score::Mate(moves) => Score::Mate(-moves),
+        }
+    }
+
+    pub fn from_search(info: &SearchInfo, player: Player) -> Self {
+        let score = match info.get_mate_in() {
+            Some(moves) => Score::Mate(moves),
+            None => Score::Centipawns(info.score),
+        };
+
+        score.for_player(player)
+    }
+
+    // white's share of the evaluation bar
+    pub fn get_bar_fraction(&self) -> f32 {
+        match *self {
+            Score::Centipawns(centipawns) => 0.5 + (centipawns.clamp(-1000, 1000) as f32 / 2000.),
+            Score::Mate(moves) => if moves > 0 { 1. } else { 0. },
+        }
+    }
+
+    // white's chance of winning in percent, following the lichess model
+    pub fn get_win_percent(&self) -> f32 {
+        match *self {
+            Score::Centipawns(centipawns) => 50. + 50. * (2. / (1. + (-0.00368208 * centipawns as f32).exp()) - 1.),
+            Score::Mate(moves) => if moves > 0 { 100. } else { 0. },
+        }
+    }
+
+    pub fn format(&self) -> String {
+        match *self {
+            Score::Centipawns(centipawns) => format!("{:+.2}", centipawns as f32 / 100.),
+            Score::Mate(moves) if moves > 0 => format!("M{}", moves),
+            Score::Mate(moves) => format!("-M{}", -moves),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnalysisLine {
+    pub score: Score,
+    pub pv: Vec<String>,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Analysis {
+    pub depth: u8,
+    pub nodes: u64,
+    pub lines: Vec<AnalysisLine>,
+}
+
+impl Analysis {
+    fn from_search(infos: &[SearchInfo], player: Player) -> Self {
+        Self {
+            depth: infos.first().map(|info| info.depth).unwrap_or(0),
+            nodes: infos.first().map(|info| info.nodes).unwrap_or(0),
+            lines: infos.iter().map(|info| AnalysisLine {
+                score: Score::from_search(info, player),
+                pv: info.pv.clone(),
+            }).collect(),
+        }
+    }
+}
+
+enum AnalyserBackend {
+    BuiltIn {
+        stop: Arc<AtomicBool>,
+        receiver: Option<Receiver<Analysis>>,
+        // shared by the searches of every analysed position
+        table: Arc<TranspositionTable>,
+        threads: usize,
+    },
+    External {
+        engine: Box<UciEngine>,
+        is_searching: bool,
+        is_stopping: bool,
+        pending_position: Option<String>,
+    },
+}
+
+pub struct Analyser {
+    backend: AnalyserBackend,
+    player: Player,
+    pub analysis: Analysis,
+    pub error: Option<String>,
+}
+
+impl Analyser {
+    // the hash size in megabytes
+    pub fn built_in(hash_size: usize, threads: usize) -> Self {
+        Self {
+            backend: AnalyserBackend::BuiltIn {
+                stop: Arc::new(AtomicBool::new(false)),
+                receiver: None,
+                table: Arc::new(TranspositionTable::new(hash_size)),
+                threads,
+            },
+            player: Player::White,
+            analysis: Analysis::default(),
+            error: None,
+        }
+    }
+
+    pub fn external(path: &str, is_chess960: bool, threads: usize) -> Result<Self, String> {
+        let mut engine = UciEngine::start(path)?;
+
+        engine.set_threads(threads)?;
+
+        engine.set_option("MultiPV", &ANALYSIS_LINES.to_string())?;
+
+        if is_chess960 {
+            engine.set_option("UCI_Chess960", "true")?;
+        }
+
+        engine.send("ucinewgame")?;
+        engine.wait_until_ready()?;
+
+        Ok(Self {
+            backend: AnalyserBackend::External {
+                engine: Box::new(engine),
+                is_searching: false,
+                is_stopping: false,
+                pending_position: None,
+            },
+            player: Player::White,
+            analysis: Analysis::default(),
+            error: None,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        match &self.backend {
+            AnalyserBackend::BuiltIn { .. } => "Built-in",
+            AnalyserBackend::External { engine, .. } => &engine.name,
+        }
+    }
+
+    // restarts the analysis on a new position
+    pub fn start(&mut self, board: &Board, player: Player, fen: String) {
+        self.player = player;
+        self.analysis = Analysis::default();
+
+        let result = match &mut self.backend {
+            AnalyserBackend::BuiltIn { stop, receiver, table, threads } => {
+                stop.store(true, Ordering::Relaxed);
+
+                *stop = Arc::new(AtomicBool::new(false));
+
+                let (sender, new_receiver) = mpsc::channel();
+                let search_stop = stop.clone();
+                let board = board.clone();
+                let table = table.clone();
+                let threads = *threads;
+
+                thread::spawn(move || {
+                    let limits = SearchLimits { depth: MAX_ANALYSIS_DEPTH, movetime: None };
+
+                    let mut search = Search::new(search_stop, table);
+                    search.threads = threads;
+
+                    search.run_multi_pv(&board, player, limits, ANALYSIS_LINES, |infos| {
+                        let _ = sender.send(Analysis::from_search(infos, player));
+                    });
+                });
+
+                *receiver = Some(new_receiver);
+
+                Ok(())
+            },
+            AnalyserBackend::External { engine, is_searching, is_stopping, pending_position } => {
+                *pending_position = Some(fen);
+
+                if *is_searching && !*is_stopping {
+                    *is_stopping = true;
+
+                    engine.send("stop")
+                } else {
+                    Ok(())
+                }
+            },
+        };
+
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+
+        self.send_pending_position();
+    }
+
+    fn send_pending_position(&mut self) {
+        let AnalyserBackend::External { engine, is_searching, is_stopping, pending_position } = &mut self.backend else {
+            return;
+        };
+
+        if *is_stopping {
+            return;
+        }
+
+        let Some(fen) = pending_position.take() else {
+            return;
+        };
+
+        let result = engine.send(&format!("position fen {}", fen)).and_then(|_| engine.send("go infinite"));
+
+        match result {
+            Ok(()) => *is_searching = true,
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    // returns true when the analysis changed
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        match &mut self.backend {
+            AnalyserBackend::BuiltIn { receiver, .. } => {
+                if let Some(receiver) = receiver {
+                    while let Ok(analysis) = receiver.try_recv() {
+                        self.analysis = analysis;
+                        changed = true;
+                    }
+                }
+            },
+            AnalyserBackend::External { engine, is_searching, is_stopping, .. } => {
+                while let Some(line) = engine.try_read_line() {
+                    if line.starts_with("bestmove") {
+                        *is_searching = false;
+                        *is_stopping = false;
+
+                        continue;
+                    }
+
+                    if *is_stopping {
+                        continue;
+                    }
+
+                    let Some(info) = uci_engine::parse_info(&line) else {
+                        continue;
+                    };
+
+                    if info.multipv == 0 || info.multipv > ANALYSIS_LINES {
+                        continue;
+                    }
+
+                    let lines = &mut self.analysis.lines;
+
+                    if info.multipv == 1 && info.depth != self.analysis.depth {
+                        lines.truncate(1);
+                    }
+
+                    if lines.len() < info.multipv {
+                        lines.resize(info.multipv, AnalysisLine { score: Score::Centipawns(0), pv: Vec::new() });
+                    }
+
+                    lines[info.multipv - 1] = AnalysisLine {
+                        score: info.score.for_player(self.player),
+                        pv: info.pv,
+                    };
+
+                    if info.multipv == 1 {
+                        self.analysis.depth = info.depth;
+                        self.analysis.nodes = info.nodes;
+                    }
+
+                    changed = true;
+                }
+            },
+        }
+
+        self.send_pending_position();
+
+        changed
+    }
+}
+
+impl Drop for Analyser {
+    fn drop(&mut self) {
+        match &mut self.backend {
+            AnalyserBackend::BuiltIn { stop, .. } => stop.store(true, Ordering::Relaxed),
+            AnalyserBackend::External { engine, .. } => {
+                let _ = engine.send("stop");
+            },
+        }
+    }
+}
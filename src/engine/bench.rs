@@ -0,0 +1,66 @@
+use std::{sync::{atomic::AtomicBool, Arc}, time::Instant};
+
+use crate::game::fen;
+
+use super::{search::{Search, SearchLimits}, transposition::{TranspositionTable, DEFAULT_HASH_SIZE}};
+
+const DEFAULT_DEPTH: u8 = 5;
+
+const POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r2q1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 9",
+    "2r3k1/pp3ppp/4p3/3n4/3P4/P4N2/1P3PPP/2R3K1 b - - 0 24",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/8/4k3/8/2p5/8/B2K4/8 w - - 0 1",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+];
+
+// searches fixed positions to a fixed depth, the node count changes only when the search does
+// e.g. `rust_games --bench 6`, a thread count after the depth measures lazy smp but is not repeatable
+pub fn run(args: &[String]) -> Result<(), String> {
+    let depth = match args.first() {
+        Some(depth) => depth.parse().map_err(|_| format!("invalid depth {}", depth))?,
+        None => DEFAULT_DEPTH,
+    };
+
+    let threads = match args.get(1) {
+        Some(threads) => threads.parse().ok().filter(|threads| *threads > 0).ok_or(format!("invalid thread count {}", threads))?,
+        None => 1,
+    };
+
+    let table = Arc::new(TranspositionTable::new(DEFAULT_HASH_SIZE));
+    let mut search = Search::new(Arc::new(AtomicBool::new(false)), table.clone());
+    search.threads = threads;
+
+    let mut total_nodes = 0;
+    let start = Instant::now();
+
+    for (index, position) in POSITIONS.iter().enumerate() {
+        let position = fen::parse_fen(position)?;
+
+        // every position starts from an empty table so the count does not depend on the order
+        table.clear();
+
+        let info = search.run(&position.board, position.current_player, SearchLimits { depth, movetime: None }, |_| ());
+        let nodes = info.as_ref().map_or(0, |info| info.nodes);
+
+        println!(
+            "Position {}: {} nodes, best move {}",
+            index + 1,
+            nodes,
+            info.as_ref().and_then(|info| info.pv.first().cloned()).unwrap_or("none".into()),
+        );
+
+        total_nodes += nodes;
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!("Nodes: {}", total_nodes);
+    println!("Time: {:.2}s", elapsed);
+    println!("NPS: {}", (total_nodes as f64 / elapsed.max(0.001)) as u64);
+
+    Ok(())
+}
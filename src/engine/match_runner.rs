@@ -0,0 +1,639 @@
+use std::{fs::{self, File}, io::Write, thread, time::{Duration, Instant}};
+
+use crate::{game::{clock::Clock, fen, notation, piece::Player}, server::server_game::{GameStatus, ServerGame}};
+
+use super::{analysis::Score, sprt::{MatchScore, Sprt, SprtResult}, tablebase::{Tablebase, Wdl}, transposition::DEFAULT_HASH_SIZE, EngineOptions, EnginePlayer, EngineRequest, MAX_LEVEL};
+
+const DEFAULT_LEVEL: u8 = 3;
+const DEFAULT_BOOK_PLIES: usize = 16;
+
+// time an engine may go over its clock before it loses, or spend on a move without a clock
+const TIME_MARGIN: f32 = 0.1;
+const MOVE_TIMEOUT: Duration = Duration::from_secs(60);
+
+const MATE_CENTIPAWNS: i32 = 100000;
+const RESIGN_PLIES: usize = 4;
+const DRAW_PLIES: usize = 8;
+const DRAW_MIN_PLIES: usize = 80;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum EngineSpec {
+    BuiltIn(u8),
+    External(String),
+}
+
+impl EngineSpec {
+    // "builtin", "builtin:N" or the path of a uci engine
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.strip_prefix("builtin") {
+            Some("") => Ok(EngineSpec::BuiltIn(DEFAULT_LEVEL)),
+            Some(level) => level.strip_prefix(':')
+                .and_then(|level| level.parse::<u8>().ok())
+                .filter(|level| (1..=MAX_LEVEL).contains(level))
+                .map(EngineSpec::BuiltIn)
+                .ok_or(format!("expected builtin:1-{}, not '{}'", MAX_LEVEL, text)),
+            None => Ok(EngineSpec::External(text.into())),
+        }
+    }
+
+    fn start(&self, options: &EngineOptions) -> Result<EnginePlayer, String> {
+        match self {
+            EngineSpec::BuiltIn(level) => Ok(EnginePlayer::built_in(*level).with_options(options.clone())),
+            EngineSpec::External(path) => EnginePlayer::external(path, false, options.threads),
+        }
+    }
+}
+
+pub struct MatchOptions {
+    pub engines: [EngineSpec; 2],
+    pub games: u32,
+    pub book: Option<String>,
+    pub book_plies: usize,
+    // base and increment in seconds
+    pub time_control: Option<(f32, f32)>,
+    pub pgn: Option<String>,
+    pub max_moves: Option<usize>,
+    pub resign_score: Option<i32>,
+    pub draw_score: Option<i32>,
+    // a syzygy directory to end games the tables decide
+    pub tablebase: Option<String>,
+    // for the built-in engines, in megabytes
+    pub hash_size: usize,
+    // search threads of every engine
+    pub threads: usize,
+    pub sprt: Option<Sprt>,
+}
+
+impl MatchOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut engines = Vec::new();
+        let mut options = Self {
+            engines: [EngineSpec::BuiltIn(DEFAULT_LEVEL), EngineSpec::BuiltIn(DEFAULT_LEVEL)],
+            games: 2,
+            book: None,
+            book_plies: DEFAULT_BOOK_PLIES,
+            time_control: None,
+            pgn: None,
+            max_moves: None,
+            resign_score: None,
+            draw_score: None,
+            tablebase: None,
+            hash_size: DEFAULT_HASH_SIZE,
+            threads: 1,
+            sprt: None,
+        };
+
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                engines.push(EngineSpec::parse(arg)?);
+
+                continue;
+            }
+
+            let value = args.next().ok_or(format!("{} needs a value", arg))?;
+            let invalid = || format!("invalid value '{}' for {}", value, arg);
+
+            match arg.as_str() {
+                "--games" => options.games = value.parse().ok().filter(|games| *games > 0).ok_or_else(invalid)?,
+                "--book" => options.book = Some(value.clone()),
+                "--book-plies" => options.book_plies = value.parse().map_err(|_| invalid())?,
+                "--tc" => {
+                    let (base, increment) = value.split_once('+').unwrap_or((value, "0"));
+
+                    options.time_control = Some((base.parse().map_err(|_| invalid())?, increment.parse().map_err(|_| invalid())?));
+                },
+                "--pgn" => options.pgn = Some(value.clone()),
+                "--max-moves" => options.max_moves = Some(value.parse().map_err(|_| invalid())?),
+                "--resign" => options.resign_score = Some(value.parse().map_err(|_| invalid())?),
+                "--draw" => options.draw_score = Some(value.parse().map_err(|_| invalid())?),
+                "--tb" => options.tablebase = Some(value.clone()),
+                "--hash" => options.hash_size = value.parse().map_err(|_| invalid())?,
+                "--threads" => options.threads = value.parse().ok().filter(|threads| *threads > 0).ok_or_else(invalid)?,
+                "--sprt" => {
+                    let values = value.split(',').map(|number| number.parse::<f64>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+
+                    options.sprt = Some(match values.as_slice() {
+                        [elo0, elo1] => Sprt::new(*elo0, *elo1),
+                        [elo0, elo1, alpha, beta] => Sprt { elo0: *elo0, elo1: *elo1, alpha: *alpha, beta: *beta },
+                        _ => return Err(invalid()),
+                    });
+                },
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+
+        options.engines = engines.try_into().map_err(|_| String::from("expected two engines"))?;
+
+        Ok(options)
+    }
+}
+
+// a start position and the book moves played from it
+#[derive(Clone, Debug)]
+pub struct Opening {
+    pub fen: Option<String>,
+    pub moves: Vec<String>,
+}
+
+pub fn load_book(path: &str, plies: usize) -> Result<Vec<Opening>, String> {
+    let contents = fs::read_to_string(path).map_err(|error| format!("could not read {}: {}", path, error))?;
+
+    let openings = if path.to_lowercase().ends_with(".epd") {
+        parse_epd(&contents)?
+    } else {
+        parse_pgn(&contents, plies)?
+    };
+
+    if openings.is_empty() {
+        return Err(format!("no openings in {}", path));
+    }
+
+    Ok(openings)
+}
+
+fn parse_epd(contents: &str) -> Result<Vec<Opening>, String> {
+    let mut openings = Vec::new();
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().take(4).collect();
+
+        if fields.len() < 4 {
+            return Err(format!("invalid epd line '{}'", line));
+        }
+
+        // epd positions have no move counters
+        let fen = fields.join(" ") + " 0 1";
+
+        ServerGame::new(0, Some(fen.clone()))?;
+
+        openings.push(Opening { fen: Some(fen), moves: Vec::new() });
+    }
+
+    Ok(openings)
+}
+
+// the main line of every game, without comments and variations
+fn parse_pgn(contents: &str, plies: usize) -> Result<Vec<Opening>, String> {
+    let mut openings = Vec::new();
+    let mut fen = None;
+    let mut movetext = String::new();
+
+    let mut lines = contents.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+
+        if let Some(header) = line.strip_prefix("[FEN \"") {
+            fen = header.strip_suffix("\"]").map(String::from);
+        }
+        else if !line.starts_with('[') {
+            movetext += line;
+            movetext += "\n";
+        }
+
+        let next_is_header = lines.peek().is_none_or(|next| next.trim_start().starts_with('['));
+
+        if next_is_header && !movetext.trim().is_empty() {
+            openings.push(parse_movetext(fen.take(), &std::mem::take(&mut movetext), plies)?);
+        }
+    }
+
+    Ok(openings)
+}
+
+fn parse_movetext(fen: Option<String>, movetext: &str, plies: usize) -> Result<Opening, String> {
+    let mut game = ServerGame::new(0, fen.clone())?;
+    let mut moves = Vec::new();
+    let mut depth = 0;
+    let mut text = String::new();
+    let mut chars = movetext.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                chars.by_ref().find(|c| *c == '}');
+                text.push(' ');
+            },
+            ';' => {
+                chars.by_ref().find(|c| *c == '\n');
+                text.push(' ');
+            },
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 => text.push(c),
+            _ => (),
+        }
+    }
+
+    for token in text.split_whitespace() {
+        if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+            break;
+        }
+
+        // move numbers may be written together with the move, castling may be written with zeros
+        let token = match token.find(|c: char| !c.is_ascii_digit()) {
+            Some(index) if index > 0 && token[index..].starts_with('.') => token[index..].trim_start_matches('.'),
+            _ => token,
+        };
+
+        if token.is_empty() || token.starts_with('$') {
+            continue;
+        }
+
+        if moves.len() >= plies || game.status != GameStatus::Ongoing {
+            break;
+        }
+
+        let uci = notation::parse_san(&game.board, game.current_player, token)
+            .ok_or(format!("illegal book move '{}'", token))?;
+
+        game.play(&uci)?;
+        moves.push(uci);
+    }
+
+    Ok(Opening { fen, moves })
+}
+
+struct Outcome {
+    winner: Option<Player>,
+    termination: &'static str,
+    reason: String,
+}
+
+impl Outcome {
+    fn get_result(&self) -> &'static str {
+        match self.winner {
+            Some(Player::White) => "1-0",
+            Some(Player::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+fn get_centipawns(score: Score) -> i32 {
+    match score {
+        Score::Centipawns(centipawns) => centipawns,
+        Score::Mate(moves) if moves >= 0 => MATE_CENTIPAWNS,
+        Score::Mate(_) => -MATE_CENTIPAWNS,
+    }
+}
+
+pub struct MatchRunner {
+    options: MatchOptions,
+    openings: Vec<Opening>,
+    names: [String; 2],
+    score: MatchScore,
+    engine_options: EngineOptions,
+    tablebase: Option<Tablebase>,
+    pgn: Option<File>,
+}
+
+impl MatchRunner {
+    pub fn new(options: MatchOptions) -> Result<Self, String> {
+        let openings = match &options.book {
+            Some(path) => load_book(path, options.book_plies)?,
+            None => vec![Opening { fen: None, moves: Vec::new() }],
+        };
+
+        let engine_options = EngineOptions {
+            hash_size: options.hash_size,
+            threads: options.threads,
+            ..EngineOptions::default()
+        };
+
+        let names = [options.engines[0].start(&engine_options)?.get_name(), options.engines[1].start(&engine_options)?.get_name()];
+
+        let tablebase = match &options.tablebase {
+            Some(path) => Some(Tablebase::open(path)?),
+            None => None,
+        };
+
+        let pgn = match &options.pgn {
+            Some(path) => Some(File::create(path).map_err(|error| format!("could not create {}: {}", path, error))?),
+            None => None,
+        };
+
+        Ok(Self {
+            options,
+            openings,
+            names,
+            score: MatchScore::default(),
+            engine_options,
+            tablebase,
+            pgn,
+        })
+    }
+
+    // the engines swap colours after every game, so each opening is played from both sides
+    pub fn run(&mut self) -> Result<MatchScore, String> {
+        for round in 0..self.options.games as usize {
+            let opening = self.openings[round / 2 % self.openings.len()].clone();
+            let first_engine_color = if round % 2 == 1 { Player::Black } else { Player::White };
+
+            let (game, outcome) = self.play_game(&opening, first_engine_color)?;
+
+            match outcome.winner {
+                Some(winner) if winner == first_engine_color => self.score.wins += 1,
+                Some(_) => self.score.losses += 1,
+                None => self.score.draws += 1,
+            }
+
+            self.write_pgn(round + 1, &game, &outcome, first_engine_color)?;
+            self.print_progress(round + 1, &outcome, first_engine_color);
+
+            if let Some(result) = self.options.sprt.and_then(|sprt| sprt.get_result(&self.score)) {
+                match result {
+                    SprtResult::AcceptH0 => println!("SPRT: H0 accepted, stopping"),
+                    SprtResult::AcceptH1 => println!("SPRT: H1 accepted, stopping"),
+                }
+
+                break;
+            }
+        }
+
+        Ok(self.score)
+    }
+
+    fn play_game(&self, opening: &Opening, first_engine_color: Player) -> Result<(ServerGame, Outcome), String> {
+        let mut game = ServerGame::new(0, opening.fen.clone())?;
+
+        for uci in &opening.moves {
+            game.play(uci)?;
+        }
+
+        let specs = match first_engine_color {
+            Player::White => [&self.options.engines[0], &self.options.engines[1]],
+            Player::Black => [&self.options.engines[1], &self.options.engines[0]],
+        };
+
+        let mut engines = [specs[0].start(&self.engine_options)?, specs[1].start(&self.engine_options)?];
+        let mut clock = self.options.time_control.map(|(base, increment)| Clock {
+            remaining: [base, base],
+            increment,
+            low_time_warned: [false, false],
+        });
+
+        // white's point of view, one per engine move
+        let mut scores: Vec<Option<Score>> = Vec::new();
+
+        let outcome = loop {
+            if let Some(outcome) = self.get_game_end(&game).or_else(|| self.adjudicate(&game, &scores)) {
+                break outcome;
+            }
+
+            let player = game.current_player;
+            let engine = &mut engines[if player == Player::White { 0 } else { 1 }];
+
+            let request = EngineRequest {
+                board: game.board.clone(),
+                player,
+                start_fen: game.start_fen.clone().unwrap_or(fen::STARTING_FEN.into()),
+                moves: game.moves.iter().map(|played_move| played_move.uci.clone()).collect(),
+                fifty_move_rule: game.fifty_move_rule,
+                clock: clock.clone(),
+            };
+
+            if let Err(error) = engine.request_move(request) {
+                break Self::forfeit(player, "rules infraction", error);
+            }
+
+            let time_limit = match &clock {
+                Some(clock) => Duration::from_secs_f32(clock.get_remaining(player) + TIME_MARGIN),
+                None => MOVE_TIMEOUT,
+            };
+
+            let start = Instant::now();
+
+            let result = loop {
+                if let Some(result) = engine.poll_move() {
+                    break Some(result);
+                }
+
+                if start.elapsed() > time_limit {
+                    break None;
+                }
+
+                thread::sleep(Duration::from_millis(1));
+            };
+
+            if let Some(clock) = &mut clock {
+                clock.tick(player, start.elapsed().as_secs_f32());
+
+                if result.is_none() || clock.is_flagged(player) {
+                    break Self::time_forfeit(&game, player);
+                }
+
+                clock.add_increment(player);
+            }
+
+            let uci = match result {
+                Some(Ok(uci)) => uci,
+                Some(Err(error)) => break Self::forfeit(player, "rules infraction", error),
+                None => break Self::forfeit(player, "time forfeit", "no move in time".into()),
+            };
+
+            if let Err(error) = game.play(&uci) {
+                break Self::forfeit(player, "rules infraction", error);
+            }
+
+            scores.push(engine.last_score);
+        };
+
+        Ok((game, outcome))
+    }
+
+    fn forfeit(player: Player, termination: &'static str, reason: String) -> Outcome {
+        Outcome {
+            winner: Some(player.get_opponent()),
+            termination,
+            reason: format!("{} {}", if player == Player::White { "White" } else { "Black" }, reason),
+        }
+    }
+
+    // a flag is a draw if the opponent could never mate
+    fn time_forfeit(game: &ServerGame, player: Player) -> Outcome {
+        let opponent = player.get_opponent();
+        let winner = game.board.get_total_pieces().has_mating_material(opponent).then_some(opponent);
+
+        Outcome {
+            winner,
+            termination: "time forfeit",
+            reason: format!("{} ran out of time", if player == Player::White { "White" } else { "Black" }),
+        }
+    }
+
+    fn get_game_end(&self, game: &ServerGame) -> Option<Outcome> {
+        let reason = match game.status {
+            GameStatus::Ongoing => return None,
+            GameStatus::Checkmate => "checkmate",
+            GameStatus::Stalemate => "stalemate",
+            GameStatus::InsufficientMaterial => "insufficient material",
+            GameStatus::Repetition => "threefold repetition",
+            GameStatus::FiftyMoveRule => "fifty-move rule",
+        };
+
+        Some(Outcome {
+            winner: game.winner,
+            termination: "normal",
+            reason: reason.into(),
+        })
+    }
+
+    fn adjudicate(&self, game: &ServerGame, scores: &[Option<Score>]) -> Option<Outcome> {
+        let adjudication = |winner, reason: &str| Some(Outcome {
+            winner,
+            termination: "adjudication",
+            reason: reason.into(),
+        });
+
+        if let Some(probe) = self.tablebase.as_ref().and_then(|tablebase| tablebase.probe(&game.board, game.current_player, game.fifty_move_rule)) {
+            return match probe.wdl {
+                Wdl::Win => adjudication(Some(game.current_player), "Tablebase win"),
+                Wdl::Loss => adjudication(Some(game.current_player.get_opponent()), "Tablebase win"),
+                Wdl::Draw => adjudication(None, "Tablebase draw"),
+            };
+        }
+
+        // both engines have to agree on the last few moves
+        let last_scores = |plies: usize| -> Option<Vec<i32>> {
+            if scores.len() < plies {
+                return None;
+            }
+
+            scores[scores.len() - plies..].iter().map(|score| score.map(get_centipawns)).collect()
+        };
+
+        if let (Some(threshold), Some(last)) = (self.options.resign_score, last_scores(RESIGN_PLIES)) {
+            if last.iter().all(|centipawns| *centipawns >= threshold) {
+                return adjudication(Some(Player::White), "Black resigns");
+            }
+
+            if last.iter().all(|centipawns| *centipawns <= -threshold) {
+                return adjudication(Some(Player::Black), "White resigns");
+            }
+        }
+
+        if let (Some(threshold), Some(last)) = (self.options.draw_score, last_scores(DRAW_PLIES)) {
+            if game.moves.len() >= DRAW_MIN_PLIES && last.iter().all(|centipawns| centipawns.abs() <= threshold) {
+                return adjudication(None, "Draw by adjudication");
+            }
+        }
+
+        if self.options.max_moves.is_some_and(|max_moves| game.moves.len() >= max_moves * 2) {
+            return adjudication(None, "Move limit reached");
+        }
+
+        None
+    }
+
+    fn write_pgn(&mut self, round: usize, game: &ServerGame, outcome: &Outcome, first_engine_color: Player) -> Result<(), String> {
+        let Some(file) = &mut self.pgn else {
+            return Ok(());
+        };
+
+        let (white, black) = match first_engine_color {
+            Player::White => (&self.names[0], &self.names[1]),
+            Player::Black => (&self.names[1], &self.names[0]),
+        };
+
+        let mut pgn = format!(
+            "[Event \"Engine match\"]\n[Site \"rust_games\"]\n[Round \"{}\"]\n[White \"{}\"]\n[Black \"{}\"]\n[Result \"{}\"]\n",
+            round, white, black, outcome.get_result(),
+        );
+
+        if let Some(fen) = &game.start_fen {
+            pgn += &format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", fen);
+        }
+
+        pgn += &format!("[Termination \"{}\"]\n\n", outcome.termination);
+        pgn += &game.get_movetext(&format!("{{{}}} {}", outcome.reason, outcome.get_result()));
+        pgn += "\n\n";
+
+        file.write_all(pgn.as_bytes()).map_err(|error| error.to_string())
+    }
+
+    fn print_progress(&self, round: usize, outcome: &Outcome, first_engine_color: Player) {
+        let (white, black) = match first_engine_color {
+            Player::White => (&self.names[0], &self.names[1]),
+            Player::Black => (&self.names[1], &self.names[0]),
+        };
+
+        println!("Game {} ({} vs {}): {} {{{}}}", round, white, black, outcome.get_result(), outcome.reason);
+        println!(
+            "Score of {} vs {}: {} - {} - {} [{:.3}] {}",
+            self.names[0], self.names[1], self.score.wins, self.score.losses, self.score.draws, self.score.get_fraction(), self.score.get_games(),
+        );
+
+        if let Some((elo, margin)) = self.score.get_elo() {
+            println!("Elo difference: {:.1} +/- {:.1}", elo, margin);
+        }
+
+        if let Some(sprt) = &self.options.sprt {
+            let (lower, upper) = sprt.get_bounds();
+
+            println!("SPRT: llr {:.2} ({:.2}, {:.2}) [{}, {}]", sprt.get_llr(&self.score), lower, upper, sprt.elo0, sprt.elo1);
+        }
+    }
+}
+
+// runs headless, e.g. `rust_games --match builtin:2 builtin:4 --games 100 --tc 10+0.1 --sprt 0,20`
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = MatchOptions::parse(args)?;
+
+    MatchRunner::new(options)?.run()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_main_line_of_pgn_games() {
+        let pgn = "[Event \"First\"]\n[Result \"*\"]\n\n\
+            1. e4 {best by test} e5 (1... c5 2. Nf3) 2. Nf3 $1 Nc6 ; a comment\n\
+            3. Bc4 Nf6 4. 0-0 *\n\n\
+            [Event \"Second\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1\"]\n\n\
+            1.O-O-O Kf7 2.Rd7+ 1-0\n";
+
+        let openings = parse_pgn(pgn, 20).unwrap();
+
+        assert_eq!(openings.len(), 2);
+
+        assert_eq!(openings[0].fen, None);
+        assert_eq!(openings[0].moves, ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"]);
+
+        assert_eq!(openings[1].fen.as_deref(), Some("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1"));
+        assert_eq!(openings[1].moves, ["e1c1", "e8f7", "d1d7"]);
+    }
+
+    #[test]
+    fn stops_pgn_games_after_the_book_plies() {
+        let openings = parse_pgn("1. e4 e5 2. Nf3 Nc6 *\n", 3).unwrap();
+
+        assert_eq!(openings[0].moves, ["e2e4", "e7e5", "g1f3"]);
+    }
+
+    #[test]
+    fn rejects_illegal_book_moves() {
+        assert!(parse_pgn("1. e4 e4 *\n", 10).is_err());
+    }
+
+    #[test]
+    fn parses_epd_positions() {
+        let epd = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - id \"e4\";\n\n\
+            4k3/8/8/8/8/8/8/4K2R w K - bm O-O;\n";
+
+        let openings = parse_epd(epd).unwrap();
+
+        assert_eq!(openings.len(), 2);
+        assert_eq!(openings[0].fen.as_deref(), Some("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"));
+        assert_eq!(openings[1].fen.as_deref(), Some("4k3/8/8/8/8/8/8/4K2R w K - 0 1"));
+        assert!(openings.iter().all(|opening| opening.moves.is_empty()));
+
+        assert!(parse_epd("4k3/8/8/8 w\n").is_err());
+    }
+}
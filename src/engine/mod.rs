@@ -0,0 +1,270 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver, TryRecvError}, Arc}, thread, time::Duration};
+
+use analysis::Score;
+use opening_book::OpeningBook;
+use search::{Search, SearchLimits};
+use tablebase::Tablebase;
+use transposition::{TranspositionTable, DEFAULT_HASH_SIZE};
+use uci_engine::UciEngine;
+
+use crate::game::{board::Board, clock::Clock, piece::Player};
+
+pub mod analysis;
+pub mod bench;
+pub mod evaluation;
+pub mod match_runner;
+pub mod opening_book;
+pub mod review;
+pub mod search;
+pub mod sprt;
+pub mod tablebase;
+pub mod transposition;
+pub mod uci_engine;
+
+pub const MAX_LEVEL: u8 = 6;
+
+const EXTERNAL_MOVETIME: u64 = 1000;
+
+pub fn get_level_limits(level: u8) -> SearchLimits {
+    let level = level.clamp(1, MAX_LEVEL);
+
+    SearchLimits {
+        depth: level,
+        movetime: Some(Duration::from_millis(300 * level as u64)),
+    }
+}
+
+// what the built-in engine plays with besides its level
+#[derive(Clone)]
+pub struct EngineOptions {
+    // played from while the position is in the book
+    pub book: Option<Arc<OpeningBook>>,
+    // played perfectly once the tables cover the position, and probed in the search
+    pub tablebase: Option<Arc<Tablebase>>,
+    // the transposition table in megabytes
+    pub hash_size: usize,
+    // search threads, also passed to external engines
+    pub threads: usize,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            book: None,
+            tablebase: None,
+            hash_size: DEFAULT_HASH_SIZE,
+            threads: 1,
+        }
+    }
+}
+
+pub struct EngineRequest {
+    pub board: Board,
+    pub player: Player,
+    // uci engines are sent the moves since the start so they can detect repetitions
+    pub start_fen: String,
+    pub moves: Vec<String>,
+    pub fifty_move_rule: u8,
+    pub clock: Option<Clock>,
+}
+
+enum EngineBackend {
+    BuiltIn(u8),
+    External(Box<UciEngine>),
+}
+
+pub struct EnginePlayer {
+    backend: EngineBackend,
+    search_result: Option<Receiver<Option<(String, Score)>>>,
+    waiting_for_bestmove: bool,
+    stop: Arc<AtomicBool>,
+    player: Player,
+    options: EngineOptions,
+    // kept between moves, made on the first search
+    table: Option<Arc<TranspositionTable>>,
+    // a book or tablebase move, played without searching
+    ready_move: Option<String>,
+    // the evaluation behind the last move, from white's point of view
+    pub last_score: Option<Score>,
+}
+
+impl EnginePlayer {
+    pub fn built_in(level: u8) -> Self {
+        Self {
+            backend: EngineBackend::BuiltIn(level),
+            search_result: None,
+            waiting_for_bestmove: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            player: Player::White,
+            options: EngineOptions::default(),
+            table: None,
+            ready_move: None,
+            last_score: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: EngineOptions) -> Self {
+        self.options = options;
+
+        self
+    }
+
+    pub fn external(path: &str, is_chess960: bool, threads: usize) -> Result<Self, String> {
+        let mut engine = UciEngine::start(path)?;
+
+        engine.set_threads(threads)?;
+
+        if is_chess960 {
+            engine.set_option("UCI_Chess960", "true")?;
+        }
+
+        engine.send("ucinewgame")?;
+        engine.wait_until_ready()?;
+
+        Ok(Self {
+            backend: EngineBackend::External(Box::new(engine)),
+            search_result: None,
+            waiting_for_bestmove: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            player: Player::White,
+            options: EngineOptions::default(),
+            table: None,
+            ready_move: None,
+            last_score: None,
+        })
+    }
+
+    pub fn get_name(&self) -> String {
+        match &self.backend {
+            EngineBackend::BuiltIn(level) => format!("Built-in {}", level),
+            EngineBackend::External(engine) => engine.name.clone(),
+        }
+    }
+
+    pub fn is_thinking(&self) -> bool {
+        self.search_result.is_some() || self.waiting_for_bestmove || self.ready_move.is_some()
+    }
+
+    pub fn request_move(&mut self, request: EngineRequest) -> Result<(), String> {
+        self.player = request.player;
+        self.last_score = None;
+
+        if let EngineBackend::BuiltIn(_) = &self.backend {
+            self.ready_move = self.options.book.as_ref()
+                .and_then(|book| book.pick_move(&request.board, request.player))
+                .or_else(|| self.options.tablebase.as_ref().and_then(|tablebase| tablebase.get_best_move(&request.board, request.player, request.fifty_move_rule)));
+
+            if self.ready_move.is_some() {
+                return Ok(());
+            }
+        }
+
+        match &mut self.backend {
+            EngineBackend::BuiltIn(level) => {
+                let mut limits = get_level_limits(*level);
+
+                if let Some(clock) = &request.clock {
+                    let budget = clock.get_remaining(request.player) / 30. + clock.increment / 2.;
+
+                    limits.movetime = limits.movetime.map(|movetime| movetime.min(Duration::from_secs_f32(budget.max(0.05))));
+                }
+
+                let (sender, receiver) = mpsc::channel();
+                let stop = self.stop.clone();
+                let tablebase = self.options.tablebase.clone();
+                let threads = self.options.threads;
+                let table = self.table.get_or_insert_with(|| Arc::new(TranspositionTable::new(self.options.hash_size))).clone();
+
+                thread::spawn(move || {
+                    let mut search = Search::new(stop, table);
+                    search.tablebase = tablebase;
+                    search.threads = threads;
+
+                    let result = search.run(&request.board, request.player, limits, |_| ());
+
+                    let _ = sender.send(result.and_then(|info| Some((info.pv.first()?.clone(), Score::from_search(&info, request.player)))));
+                });
+
+                self.search_result = Some(receiver);
+            },
+            EngineBackend::External(engine) => {
+                let mut position = format!("position fen {}", request.start_fen);
+
+                if !request.moves.is_empty() {
+                    position += &format!(" moves {}", request.moves.join(" "));
+                }
+
+                engine.send(&position)?;
+
+                let go = match &request.clock {
+                    Some(clock) => format!(
+                        "go wtime {} btime {} winc {} binc {}",
+                        (clock.get_remaining(Player::White) * 1000.) as u64,
+                        (clock.get_remaining(Player::Black) * 1000.) as u64,
+                        (clock.increment * 1000.) as u64,
+                        (clock.increment * 1000.) as u64,
+                    ),
+                    None => format!("go movetime {}", EXTERNAL_MOVETIME),
+                };
+
+                engine.send(&go)?;
+
+                self.waiting_for_bestmove = true;
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn poll_move(&mut self) -> Option<Result<String, String>> {
+        if let Some(ready_move) = self.ready_move.take() {
+            return Some(Ok(ready_move));
+        }
+
+        if let Some(receiver) = &self.search_result {
+            return match receiver.try_recv() {
+                Ok(result) => {
+                    self.search_result = None;
+
+                    let result = result.map(|(uci, score)| {
+                        self.last_score = Some(score);
+
+                        uci
+                    });
+
+                    Some(result.ok_or(String::from("engine found no move")))
+                },
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => {
+                    self.search_result = None;
+
+                    Some(Err("engine search stopped".into()))
+                },
+            };
+        }
+
+        if let EngineBackend::External(engine) = &mut self.backend {
+            while self.waiting_for_bestmove {
+                let line = engine.try_read_line()?;
+
+                if let Some(info) = uci_engine::parse_info(&line).filter(|info| info.multipv == 1) {
+                    self.last_score = Some(info.score.for_player(self.player));
+                }
+
+                if let Some(rest) = line.strip_prefix("bestmove ") {
+                    self.waiting_for_bestmove = false;
+
+                    return Some(rest.split_whitespace().next().map(String::from).ok_or(String::from("engine sent an empty bestmove")));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl Drop for EnginePlayer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,95 @@
+use std::fs;
+
+use rand::seq::SliceRandom;
+
+use crate::game::{board::Board, notation, piece::{PieceType, Player}, position::Position, zobrist};
+
+const ENTRY_SIZE: usize = 16;
+
+struct BookEntry {
+    key: u64,
+    raw_move: u16,
+    weight: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct BookMove {
+    pub uci: String,
+    pub weight: u16,
+}
+
+// a polyglot .bin book, entries are sorted by the position hash
+pub struct OpeningBook {
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|error| format!("could not read {}: {}", path, error))?;
+
+        if bytes.len() % ENTRY_SIZE != 0 {
+            return Err(format!("{} is not a polyglot book", path));
+        }
+
+        let entries = bytes.chunks_exact(ENTRY_SIZE)
+            .map(|entry| BookEntry {
+                key: u64::from_be_bytes(entry[0..8].try_into().unwrap()),
+                raw_move: u16::from_be_bytes([entry[8], entry[9]]),
+                weight: u16::from_be_bytes([entry[10], entry[11]]),
+            })
+            .collect();
+
+        Ok(Self { entries })
+    }
+
+    // squares count from a1, castling is written as the king taking its rook
+    fn get_uci(board: &Board, player: Player, raw_move: u16) -> Option<String> {
+        let get_position = |square: u16| Position::new((square % 8) as i32, 7 - (square / 8) as i32);
+
+        let from_pos = get_position((raw_move >> 6) & 63);
+        let to_pos = get_position(raw_move & 63);
+        let promotion = match (raw_move >> 12) & 7 {
+            0 => None,
+            1 => Some(PieceType::Knight),
+            2 => Some(PieceType::Bishop),
+            3 => Some(PieceType::Rook),
+            4 => Some(PieceType::Queen),
+            _ => return None,
+        };
+
+        let piece_move = board.find_move(player, from_pos, to_pos)?;
+
+        Some(notation::get_uci(from_pos, piece_move.get_to_pos(), promotion))
+    }
+
+    // the legal book moves of the position, most played first
+    pub fn get_moves(&self, board: &Board, player: Player) -> Vec<BookMove> {
+        let key = zobrist::get_polyglot_hash(board, player);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+
+        let mut book_moves: Vec<BookMove> = self.entries[start..].iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| Some(BookMove {
+                uci: Self::get_uci(board, player, entry.raw_move)?,
+                weight: entry.weight,
+            }))
+            .collect();
+
+        book_moves.sort_by_key(|book_move| std::cmp::Reverse(book_move.weight));
+
+        book_moves
+    }
+
+    // a random book move, picked in proportion to the weights
+    pub fn pick_move(&self, board: &Board, player: Player) -> Option<String> {
+        let book_moves = self.get_moves(board, player);
+
+        if book_moves.iter().all(|book_move| book_move.weight == 0) {
+            return book_moves.first().map(|book_move| book_move.uci.clone());
+        }
+
+        book_moves.choose_weighted(&mut rand::thread_rng(), |book_move| book_move.weight as u32)
+            .ok()
+            .map(|book_move| book_move.uci.clone())
+    }
+}
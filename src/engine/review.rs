@@ -0,0 +1,204 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver, Sender}, Arc}, thread, time::Duration};
+
+use crate::game::{board::Board, piece::Player};
+
+use super::{analysis::Score, search::{Search, SearchLimits}, transposition::{TranspositionTable, DEFAULT_HASH_SIZE}};
+
+const REVIEW_LIMITS: SearchLimits = SearchLimits {
+    depth: 5,
+    movetime: Some(Duration::from_millis(400)),
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoveClass {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    // classifies by the winning chances the mover lost, in percentage points
+    pub fn from_loss(loss: f32) -> Self {
+        if loss >= 30. {
+            MoveClass::Blunder
+        } else if loss >= 20. {
+            MoveClass::Mistake
+        } else if loss >= 10. {
+            MoveClass::Inaccuracy
+        } else {
+            MoveClass::Good
+        }
+    }
+
+    pub fn get_symbol(&self) -> &str {
+        match self {
+            MoveClass::Best => "!",
+            MoveClass::Good => "",
+            MoveClass::Inaccuracy => "?!",
+            MoveClass::Mistake => "?",
+            MoveClass::Blunder => "??",
+        }
+    }
+
+    pub fn is_mistake(&self) -> bool {
+        matches!(self, MoveClass::Inaccuracy | MoveClass::Mistake | MoveClass::Blunder)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PositionEval {
+    pub score: Score,
+    pub best_move: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReviewedMove {
+    pub class: MoveClass,
+    pub loss: f32,
+    pub best_move: Option<String>,
+}
+
+pub struct GameReview {
+    // one entry per position, starting with the position before the first move
+    pub evals: Vec<Option<PositionEval>>,
+    pub played_moves: Vec<String>,
+    pub players: Vec<Player>,
+    position_sender: Sender<(usize, Board, Player)>,
+    receiver: Receiver<(usize, PositionEval)>,
+    stop: Arc<AtomicBool>,
+}
+
+impl GameReview {
+    // positions hold every board with its side to move, played_moves the uci moves between them
+    pub fn start(positions: Vec<(Board, Player)>, played_moves: Vec<String>) -> Self {
+        let (position_sender, position_receiver) = mpsc::channel::<(usize, Board, Player)>();
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let search_stop = stop.clone();
+
+        thread::spawn(move || {
+            let table = Arc::new(TranspositionTable::new(DEFAULT_HASH_SIZE));
+
+            for (ply, board, player) in position_receiver {
+                if search_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let eval = match Search::new(search_stop.clone(), table.clone()).run(&board, player, REVIEW_LIMITS, |_| ()) {
+                    Some(info) => PositionEval {
+                        score: Score::from_search(&info, player),
+                        best_move: info.pv.first().cloned(),
+                    },
+                    None => PositionEval {
+                        score: if board.get_check(player) { Score::Mate(-1).for_player(player) } else { Score::Centipawns(0) },
+                        best_move: None,
+                    },
+                };
+
+                if sender.send((ply, eval)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut review = Self {
+            evals: Vec::new(),
+            played_moves,
+            players: Vec::new(),
+            position_sender,
+            receiver,
+            stop,
+        };
+
+        for (board, player) in positions {
+            review.queue_position(board, player);
+        }
+
+        review
+    }
+
+    // adds the position reached by `played_move` from the last one
+    pub fn add_position(&mut self, board: Board, player: Player, played_move: String) {
+        self.played_moves.push(played_move);
+        self.queue_position(board, player);
+    }
+
+    fn queue_position(&mut self, board: Board, player: Player) {
+        self.evals.push(None);
+        self.players.push(player);
+
+        let _ = self.position_sender.send((self.evals.len() - 1, board, player));
+    }
+
+    // returns true when new evaluations arrived
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+
+        while let Ok((ply, eval)) = self.receiver.try_recv() {
+            self.evals[ply] = Some(eval);
+            changed = true;
+        }
+
+        changed
+    }
+
+    pub fn get_progress(&self) -> (usize, usize) {
+        (self.evals.iter().filter(|eval| eval.is_some()).count(), self.evals.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.evals.iter().all(|eval| eval.is_some())
+    }
+
+    // the move that led to position `ply`
+    pub fn get_reviewed_move(&self, ply: usize) -> Option<ReviewedMove> {
+        if ply == 0 {
+            return None;
+        }
+
+        let before = self.evals.get(ply - 1)?.as_ref()?;
+        let after = self.evals.get(ply)?.as_ref()?;
+        let mover = self.players[ply - 1];
+
+        let win_before = before.score.for_player(mover).get_win_percent();
+        let win_after = after.score.for_player(mover).get_win_percent();
+        let loss = (win_before - win_after).max(0.);
+
+        let is_best = before.best_move.as_ref() == self.played_moves.get(ply - 1);
+
+        Some(ReviewedMove {
+            class: if is_best { MoveClass::Best } else { MoveClass::from_loss(loss) },
+            loss,
+            best_move: before.best_move.clone(),
+        })
+    }
+
+    // average of the lichess per-move accuracy formula
+    pub fn get_accuracy(&self, player: Player) -> Option<f32> {
+        let accuracies: Vec<f32> = (1..self.evals.len())
+            .filter(|ply| self.players[ply - 1] == player)
+            .filter_map(|ply| self.get_reviewed_move(ply))
+            .map(|reviewed_move| (103.1668 * (-0.04354 * reviewed_move.loss).exp() - 3.1669).clamp(0., 100.))
+            .collect();
+
+        if accuracies.is_empty() {
+            return None;
+        }
+
+        Some(accuracies.iter().sum::<f32>() / accuracies.len() as f32)
+    }
+
+    pub fn get_mistakes(&self) -> Vec<usize> {
+        (1..self.evals.len())
+            .filter(|ply| self.get_reviewed_move(*ply).is_some_and(|reviewed_move| reviewed_move.class.is_mistake()))
+            .collect()
+    }
+}
+
+impl Drop for GameReview {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
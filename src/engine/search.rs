@@ -0,0 +1,635 @@
+use std::{sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+use crate::game::{board::Board, notation, piece::{PieceType, Player}, piece_move::ChessMove, position::Position, zobrist};
+
+use super::{evaluation::{evaluate, get_piece_value, MATE_SCORE}, tablebase::{Tablebase, Wdl, TABLEBASE_WIN_SCORE}, transposition::{Bound, TranspositionTable}};
+
+const MAX_QUIESCENCE_DEPTH: u8 = 4;
+const MAX_PLY: usize = 128;
+
+// mate and tablebase scores count from the root, the table stores them from the node
+const WIN_BOUND: i32 = TABLEBASE_WIN_SCORE - MAX_PLY as i32;
+
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+const NULL_MOVE_REDUCTION: u8 = 2;
+const LMR_MIN_DEPTH: u8 = 3;
+const LMR_MIN_MOVES: usize = 3;
+
+#[derive(Clone)]
+pub struct SearchMove {
+    pub piece_move: Box<dyn ChessMove>,
+    pub promotion: Option<PieceType>,
+}
+
+impl SearchMove {
+    pub fn execute(&self, board: &mut Board) {
+        self.piece_move.execute(board);
+
+        if let Some(piece_type) = self.promotion {
+            board.promote(self.piece_move.get_to_pos(), piece_type);
+        }
+    }
+
+    // from square, to square and promotion in 15 bits, for the table, killers and history
+    pub fn get_key(&self) -> u16 {
+        let get_square = |position: Position| (position.row * 8 + position.column) as u16;
+
+        let promotion = match self.promotion {
+            None => 0,
+            Some(PieceType::Knight) => 1,
+            Some(PieceType::Bishop) => 2,
+            Some(PieceType::Rook) => 3,
+            Some(_) => 4,
+        };
+
+        get_square(self.piece_move.get_from_pos()) | get_square(self.piece_move.get_to_pos()) << 6 | promotion << 12
+    }
+
+    pub fn to_uci(&self) -> String {
+        notation::get_uci(self.piece_move.get_from_pos(), self.piece_move.get_to_pos(), self.promotion)
+    }
+
+    pub fn is_capture(&self, board: &Board) -> bool {
+        if self.piece_move.is_castle_move() {
+            return false;
+        }
+
+        let from_pos = self.piece_move.get_from_pos();
+        let to_pos = self.piece_move.get_to_pos();
+
+        board[to_pos].is_some()
+        || board[from_pos].as_ref().is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn) && from_pos.column != to_pos.column
+    }
+
+    // captures and pawn moves reset the fifty-move count
+    pub fn is_zeroing(&self, board: &Board) -> bool {
+        self.is_capture(board) || board[self.piece_move.get_from_pos()].as_ref().is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn)
+    }
+
+    fn get_order_score(&self, board: &Board) -> i32 {
+        let mut score = 0;
+
+        if self.is_capture(board) {
+            let victim = board[self.piece_move.get_to_pos()].as_ref().map(|piece| piece.get_piece_type()).unwrap_or(PieceType::Pawn);
+            let attacker = board[self.piece_move.get_from_pos()].as_ref().unwrap().get_piece_type();
+
+            score += 10_000 + get_piece_value(victim) * 10 - get_piece_value(attacker);
+        }
+
+        if let Some(piece_type) = self.promotion {
+            score += 9_000 + get_piece_value(piece_type);
+        }
+
+        score
+    }
+}
+
+pub fn get_moves(board: &Board, player: Player) -> Vec<SearchMove> {
+    let mut moves = Vec::new();
+
+    for piece_move in board.get_legal_moves(player) {
+        if piece_move.is_pawn_promotion_move() {
+            for piece_type in [PieceType::Queen, PieceType::Knight, PieceType::Rook, PieceType::Bishop] {
+                moves.push(SearchMove { piece_move: piece_move.clone(), promotion: Some(piece_type) });
+            }
+        }
+        else {
+            moves.push(SearchMove { piece_move, promotion: None });
+        }
+    }
+
+    moves
+}
+
+#[derive(Clone, Copy)]
+pub struct SearchLimits {
+    pub depth: u8,
+    pub movetime: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub score: i32,
+    pub nodes: u64,
+    pub pv: Vec<String>,
+}
+
+impl SearchInfo {
+    pub fn get_mate_in(&self) -> Option<i32> {
+        if self.score.abs() < MATE_SCORE - 1000 {
+            return None;
+        }
+
+        let plies = MATE_SCORE - self.score.abs();
+        let moves = (plies + 1) / 2;
+
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+}
+
+fn to_table_score(score: i32, ply: usize) -> i32 {
+    if score >= WIN_BOUND {
+        score + ply as i32
+    } else if score <= -WIN_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn from_table_score(score: i32, ply: usize) -> i32 {
+    if score >= WIN_BOUND {
+        score - ply as i32
+    } else if score <= -WIN_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+// without pieces a null move is often the best move, so it can not be trusted
+fn has_non_pawn_material(board: &Board, player: Player) -> bool {
+    board.state.iter().flatten().flatten().any(|piece| {
+        piece.get_color() == player && !matches!(piece.get_piece_type(), PieceType::Pawn | PieceType::King)
+    })
+}
+
+// the thread counts offered in the settings, up to the cores of the machine
+pub fn get_max_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+struct Helpers {
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+pub struct Search {
+    pub stop: Arc<AtomicBool>,
+    pub deadline: Option<Instant>,
+    pub nodes: u64,
+    pub tablebase: Option<Arc<Tablebase>>,
+    // one thread searches deterministically, more add lazy smp helpers
+    pub threads: usize,
+    // 0 for the main thread
+    helper_id: usize,
+    // what the helpers of this search have counted so far
+    helper_nodes: Arc<AtomicU64>,
+    table: Arc<TranspositionTable>,
+    // two quiet moves per ply that caused a cutoff
+    killers: Vec<[Option<u16>; 2]>,
+    // the cutoffs of quiet moves by side, from and to square
+    history: Vec<i32>,
+    aborted: bool,
+    can_stop: bool,
+}
+
+impl Search {
+    pub fn new(stop: Arc<AtomicBool>, table: Arc<TranspositionTable>) -> Self {
+        Self {
+            stop,
+            deadline: None,
+            nodes: 0,
+            tablebase: None,
+            threads: 1,
+            helper_id: 0,
+            helper_nodes: Arc::new(AtomicU64::new(0)),
+            table,
+            killers: vec![[None; 2]; MAX_PLY],
+            history: vec![0; 2 * 64 * 64],
+            aborted: false,
+            can_stop: false,
+        }
+    }
+
+    fn start(&mut self, limits: &SearchLimits) {
+        self.deadline = limits.movetime.map(|movetime| Instant::now() + movetime);
+        self.nodes = 0;
+        self.aborted = false;
+        self.killers.fill([None; 2]);
+        self.history.fill(0);
+
+        // the helpers take part in the search of their main thread
+        if self.helper_id == 0 {
+            self.table.new_search();
+        }
+    }
+
+    // lazy smp: the helpers search the same position with their own killers and history and only
+    // share the table, so the main thread finds more of the tree already searched. every other
+    // helper searches a ply deeper to spread them over the tree, the main thread decides the move
+    fn start_helpers(&self, board: &Board, player: Player, depth: u8) -> Helpers {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        self.helper_nodes.store(0, Ordering::Relaxed);
+
+        let threads = (1..self.threads).map(|helper_id| {
+            let mut helper = Search::new(stop.clone(), self.table.clone());
+            helper.tablebase = self.tablebase.clone();
+            helper.helper_id = helper_id;
+
+            let helper_nodes = self.helper_nodes.clone();
+            let board = board.clone();
+
+            thread::spawn(move || {
+                let mut counted = 0;
+
+                helper.run(&board, player, SearchLimits { depth, movetime: None }, |info| {
+                    helper_nodes.fetch_add(info.nodes - counted, Ordering::Relaxed);
+                    counted = info.nodes;
+                });
+            })
+        }).collect();
+
+        Helpers { stop, threads }
+    }
+
+    fn stop_helpers(&self, helpers: Helpers) {
+        helpers.stop.store(true, Ordering::Relaxed);
+
+        for thread in helpers.threads {
+            let _ = thread.join();
+        }
+    }
+
+    fn get_total_nodes(&self) -> u64 {
+        self.nodes + self.helper_nodes.load(Ordering::Relaxed)
+    }
+
+    pub fn run(&mut self, board: &Board, player: Player, limits: SearchLimits, mut on_info: impl FnMut(&SearchInfo)) -> Option<SearchInfo> {
+        self.start(&limits);
+
+        let helpers = self.start_helpers(board, player, limits.depth);
+
+        let mut best: Option<SearchInfo> = None;
+        let mut previous_pv: Vec<SearchMove> = Vec::new();
+
+        for depth in 1..=limits.depth.max(1) {
+            // always finish depth 1 so there is a move to play
+            self.can_stop = depth > 1;
+
+            let depth = depth.saturating_add((self.helper_id % 2) as u8);
+
+            let mut pv = Vec::new();
+            let score = self.negamax(board, player, depth, 0, -MATE_SCORE - 1, MATE_SCORE + 1, &previous_pv, &mut pv, true, false);
+
+            if self.aborted || pv.is_empty() {
+                break;
+            }
+
+            let info = SearchInfo {
+                depth,
+                score,
+                nodes: self.get_total_nodes(),
+                pv: pv.iter().map(|search_move| search_move.to_uci()).collect(),
+            };
+
+            on_info(&info);
+
+            best = Some(info);
+            previous_pv = pv;
+
+            if score.abs() >= MATE_SCORE - depth as i32 {
+                break;
+            }
+        }
+
+        self.stop_helpers(helpers);
+
+        best
+    }
+
+    // searches every root move to report the best `lines` moves with their own score and pv
+    pub fn run_multi_pv(&mut self, board: &Board, player: Player, limits: SearchLimits, lines: usize, mut on_info: impl FnMut(&[SearchInfo])) -> Vec<SearchInfo> {
+        self.start(&limits);
+
+        let helpers = self.start_helpers(board, player, limits.depth);
+
+        let mut root_moves = get_moves(board, player);
+        self.order_moves(board, player, 0, &mut root_moves, None, None);
+
+        let mut previous_lines: Vec<Vec<SearchMove>> = root_moves.into_iter().map(|search_move| vec![search_move]).collect();
+        let mut best = Vec::new();
+
+        for depth in 1..=limits.depth.max(1) {
+            self.can_stop = depth > 1;
+
+            let mut results: Vec<(i32, Vec<SearchMove>)> = Vec::new();
+
+            for previous_line in previous_lines.iter() {
+                let alpha = if results.len() >= lines { results[lines - 1].0 } else { -MATE_SCORE - 1 };
+
+                let mut next_board = board.clone();
+                previous_line[0].execute(&mut next_board);
+
+                let mut child_pv = Vec::new();
+                let is_zeroing = previous_line[0].is_zeroing(board);
+                let score = -self.negamax(&next_board, player.get_opponent(), depth - 1, 1, -MATE_SCORE - 1, -alpha, previous_line, &mut child_pv, true, is_zeroing);
+
+                if self.aborted {
+                    break;
+                }
+
+                let mut line = vec![previous_line[0].clone()];
+                line.append(&mut child_pv);
+
+                let index = results.partition_point(|(other_score, _)| *other_score >= score);
+                results.insert(index, (score, line));
+            }
+
+            if self.aborted || results.is_empty() {
+                break;
+            }
+
+            best = results.iter().take(lines).map(|(score, line)| SearchInfo {
+                depth,
+                score: *score,
+                nodes: self.get_total_nodes(),
+                pv: line.iter().map(|search_move| search_move.to_uci()).collect(),
+            }).collect();
+
+            on_info(&best);
+
+            let best_score = results[0].0;
+            previous_lines = results.into_iter().map(|(_, line)| line).collect();
+
+            if best_score.abs() >= MATE_SCORE - depth as i32 {
+                break;
+            }
+        }
+
+        self.stop_helpers(helpers);
+
+        best
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if !self.can_stop {
+            return false;
+        }
+
+        if self.aborted {
+            return true;
+        }
+
+        if self.stop.load(Ordering::Relaxed) || self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.aborted = true;
+        }
+
+        self.aborted
+    }
+
+    fn get_history_index(player: Player, key: u16) -> usize {
+        (if player == Player::White { 0 } else { 4096 }) + (key & 4095) as usize
+    }
+
+    // the table move, the previous pv, captures by value, killers and then quiet moves by history
+    fn order_moves(&self, board: &Board, player: Player, ply: usize, moves: &mut [SearchMove], table_move: Option<u16>, pv_move: Option<&SearchMove>) {
+        let pv_move = pv_move.map(|pv_move| pv_move.get_key());
+        let killers = self.killers.get(ply).copied().unwrap_or([None; 2]);
+
+        moves.sort_by_cached_key(|search_move| {
+            let key = search_move.get_key();
+
+            if Some(key) == table_move {
+                return i32::MIN;
+            }
+
+            if Some(key) == pv_move {
+                return i32::MIN + 1;
+            }
+
+            let order_score = search_move.get_order_score(board);
+
+            if order_score > 0 {
+                -order_score
+            } else if killers[0] == Some(key) {
+                -8_000
+            } else if killers[1] == Some(key) {
+                -7_000
+            } else {
+                -self.history[Self::get_history_index(player, key)].min(6_000)
+            }
+        });
+    }
+
+    fn record_cutoff(&mut self, player: Player, ply: usize, key: u16, depth: u8) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != Some(key) {
+                killers[1] = killers[0];
+                killers[0] = Some(key);
+            }
+        }
+
+        self.history[Self::get_history_index(player, key)] += depth as i32 * depth as i32;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(&mut self, board: &Board, player: Player, depth: u8, ply: usize, mut alpha: i32, beta: i32, previous_pv: &[SearchMove], pv: &mut Vec<SearchMove>, allow_null_move: bool, is_zeroing: bool) -> i32 {
+        self.nodes += 1;
+
+        if ply > 0 && self.should_stop() {
+            return 0;
+        }
+
+        // the tables know the result only right after a capture or pawn move, the root is searched as usual so there is always a move
+        if let Some(wdl) = self.tablebase.as_ref().filter(|_| ply > 0 && is_zeroing).and_then(|tablebase| tablebase.probe_wdl(board, player)) {
+            return match wdl {
+                Wdl::Win => TABLEBASE_WIN_SCORE - ply as i32,
+                Wdl::Draw => 0,
+                Wdl::Loss => -TABLEBASE_WIN_SCORE + ply as i32,
+            };
+        }
+
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(board, player, alpha, beta, 0);
+        }
+
+        let hash = zobrist::get_polyglot_hash(board, player);
+        let entry = self.table.probe(hash);
+
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let score = from_table_score(entry.score, ply);
+
+            let is_cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+
+            if is_cutoff {
+                return score;
+            }
+        }
+
+        let is_checked = board.get_check(player);
+
+        // if passing still fails high, a real move would as well
+        if allow_null_move && ply > 0 && !is_checked && depth >= NULL_MOVE_MIN_DEPTH && beta.abs() < WIN_BOUND
+            && has_non_pawn_material(board, player) && evaluate(board, player) >= beta {
+            let mut null_board = board.clone();
+            null_board.en_passant = None;
+
+            let reduced_depth = depth - 1 - NULL_MOVE_REDUCTION.min(depth - 1);
+            let score = -self.negamax(&null_board, player.get_opponent(), reduced_depth, ply + 1, -beta, -beta + 1, &[], &mut Vec::new(), false, false);
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+        }
+
+        let mut moves = get_moves(board, player);
+
+        if moves.is_empty() {
+            if is_checked {
+                return -MATE_SCORE + ply as i32;
+            }
+
+            return 0;
+        }
+
+        self.order_moves(board, player, ply, &mut moves, entry.and_then(|entry| entry.best_move), previous_pv.get(ply));
+
+        let original_alpha = alpha;
+        let mut best_score = -MATE_SCORE - 1;
+        let mut best_move = None;
+
+        for (i, search_move) in moves.into_iter().enumerate() {
+            let key = search_move.get_key();
+            let is_quiet = !search_move.is_capture(board) && search_move.promotion.is_none();
+            let is_zeroing = search_move.is_zeroing(board);
+
+            let mut next_board = board.clone();
+            search_move.execute(&mut next_board);
+
+            let mut child_pv = Vec::new();
+            let opponent = player.get_opponent();
+
+            // the first move gets the full window, later moves only have to be proven worse
+            let score = if i == 0 {
+                -self.negamax(&next_board, opponent, depth - 1, ply + 1, -beta, -alpha, previous_pv, &mut child_pv, true, is_zeroing)
+            } else {
+                let reduction = if depth >= LMR_MIN_DEPTH && i >= LMR_MIN_MOVES && is_quiet && !is_checked {
+                    if i >= 2 * LMR_MIN_MOVES { 2.min(depth - 2) } else { 1 }
+                } else {
+                    0
+                };
+
+                let mut score = -self.negamax(&next_board, opponent, depth - 1 - reduction, ply + 1, -alpha - 1, -alpha, previous_pv, &mut child_pv, true, is_zeroing);
+
+                if score > alpha && reduction > 0 && !self.aborted {
+                    child_pv.clear();
+                    score = -self.negamax(&next_board, opponent, depth - 1, ply + 1, -alpha - 1, -alpha, previous_pv, &mut child_pv, true, is_zeroing);
+                }
+
+                if score > alpha && score < beta && !self.aborted {
+                    child_pv.clear();
+                    score = -self.negamax(&next_board, opponent, depth - 1, ply + 1, -beta, -alpha, previous_pv, &mut child_pv, true, is_zeroing);
+                }
+
+                score
+            };
+
+            if self.aborted {
+                return best_score;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(key);
+            }
+
+            if score > alpha {
+                alpha = score;
+
+                pv.clear();
+                pv.push(search_move);
+                pv.append(&mut child_pv);
+            }
+
+            if alpha >= beta {
+                if is_quiet {
+                    self.record_cutoff(player, ply, key, depth);
+                }
+
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        self.table.store(hash, depth, to_table_score(best_score, ply), bound, best_move.filter(|_| bound != Bound::Upper));
+
+        best_score
+    }
+
+    fn quiescence(&mut self, board: &Board, player: Player, mut alpha: i32, beta: i32, depth: u8) -> i32 {
+        self.nodes += 1;
+
+        let stand_pat = evaluate(board, player);
+
+        if stand_pat >= beta || depth >= MAX_QUIESCENCE_DEPTH || self.should_stop() {
+            return stand_pat;
+        }
+
+        alpha = alpha.max(stand_pat);
+
+        let mut captures: Vec<SearchMove> = get_moves(board, player)
+            .into_iter()
+            .filter(|search_move| search_move.is_capture(board) || search_move.promotion == Some(PieceType::Queen))
+            .collect();
+
+        self.order_moves(board, player, MAX_PLY, &mut captures, None, None);
+
+        for search_move in captures {
+            let mut next_board = board.clone();
+            search_move.execute(&mut next_board);
+
+            let score = -self.quiescence(&next_board, player.get_opponent(), -beta, -alpha, depth + 1);
+
+            if score >= beta {
+                return score;
+            }
+
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{engine::transposition::DEFAULT_HASH_SIZE, game::fen};
+
+    use super::*;
+
+    fn search_once(text: &str, depth: u8) -> (String, i32, u64) {
+        let position = fen::parse_fen(text).unwrap();
+        let mut search = Search::new(Arc::new(AtomicBool::new(false)), Arc::new(TranspositionTable::new(DEFAULT_HASH_SIZE)));
+        search.threads = 1;
+
+        let info = search.run(&position.board, position.current_player, SearchLimits { depth, movetime: None }, |_| ()).unwrap();
+
+        (info.pv[0].clone(), info.score, info.nodes)
+    }
+
+    // bench node counts are only comparable while one thread searches the same way every time
+    #[test]
+    fn searches_the_same_way_with_one_thread() {
+        let text = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+
+        assert_eq!(search_once(text, 4), search_once(text, 4));
+    }
+}
@@ -0,0 +1,159 @@
+// results of a match from the first engine's point of view
+#[derive(Clone, Copy, Default, Debug)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl MatchScore {
+    pub fn get_games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    pub fn get_fraction(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.) / self.get_games() as f64
+    }
+
+    // variance of a single game's score
+    fn get_variance(&self) -> f64 {
+        let games = self.get_games() as f64;
+        let score = self.get_fraction();
+
+        (self.wins as f64 * (1. - score).powi(2) + self.draws as f64 * (0.5 - score).powi(2) + self.losses as f64 * score.powi(2)) / games
+    }
+
+    // the elo difference and the margin of its 95% confidence interval
+    pub fn get_elo(&self) -> Option<(f64, f64)> {
+        if self.get_games() == 0 {
+            return None;
+        }
+
+        let score = self.get_fraction();
+        let deviation = (self.get_variance() / self.get_games() as f64).sqrt();
+
+        let low = get_elo_difference(score - 1.96 * deviation);
+        let high = get_elo_difference(score + 1.96 * deviation);
+
+        Some((get_elo_difference(score), (high - low) / 2.))
+    }
+}
+
+pub fn get_elo_difference(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1. - 1e-6);
+
+    -400. * (1. / score - 1.).log10()
+}
+
+fn get_expected_score(elo: f64) -> f64 {
+    1. / (1. + 10f64.powf(-elo / 400.))
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SprtResult {
+    // the difference is closer to elo0
+    AcceptH0,
+    // the difference is closer to elo1
+    AcceptH1,
+}
+
+// sequential probability ratio test between an elo difference of elo0 and elo1
+#[derive(Clone, Copy, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    pub fn get_bounds(&self) -> (f64, f64) {
+        ((self.beta / (1. - self.alpha)).ln(), ((1. - self.beta) / self.alpha).ln())
+    }
+
+    // the log likelihood ratio, using the normal approximation of the game scores
+    pub fn get_llr(&self, score: &MatchScore) -> f64 {
+        let variance = score.get_variance();
+
+        if score.get_games() == 0 || variance <= 0. {
+            return 0.;
+        }
+
+        let score0 = get_expected_score(self.elo0);
+        let score1 = get_expected_score(self.elo1);
+
+        score.get_games() as f64 * (score1 - score0) * (2. * score.get_fraction() - score0 - score1) / (2. * variance)
+    }
+
+    pub fn get_result(&self, score: &MatchScore) -> Option<SprtResult> {
+        let llr = self.get_llr(score);
+        let (lower, upper) = self.get_bounds();
+
+        if llr <= lower {
+            Some(SprtResult::AcceptH0)
+        }
+        else if llr >= upper {
+            Some(SprtResult::AcceptH1)
+        }
+        else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(wins: u32, losses: u32, draws: u32) -> MatchScore {
+        MatchScore { wins, losses, draws }
+    }
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-3, "{} is not {}", value, expected);
+    }
+
+    #[test]
+    fn estimates_elo_and_error_bars() {
+        assert!(score(0, 0, 0).get_elo().is_none());
+
+        let (elo, margin) = score(60, 40, 0).get_elo().unwrap();
+        assert_close(elo, 70.437);
+        assert_close(margin, 70.573);
+
+        let (elo, margin) = score(30, 30, 40).get_elo().unwrap();
+        assert_close(elo, 0.);
+        assert_close(margin, 53.159);
+    }
+
+    #[test]
+    fn computes_the_log_likelihood_ratio() {
+        let sprt = Sprt::new(0., 10.);
+
+        assert_close(sprt.get_bounds().0, -2.944);
+        assert_close(sprt.get_bounds().1, 2.944);
+
+        assert_close(sprt.get_llr(&score(1000, 800, 1200)), 7.578);
+        assert_close(sprt.get_llr(&score(900, 900, 1200)), -2.070);
+        assert_close(sprt.get_llr(&score(0, 0, 0)), 0.);
+        assert_close(sprt.get_llr(&score(0, 0, 10)), 0.);
+    }
+
+    #[test]
+    fn stops_once_a_bound_is_crossed() {
+        let sprt = Sprt::new(0., 10.);
+
+        assert_eq!(sprt.get_result(&score(1000, 800, 1200)), Some(SprtResult::AcceptH1));
+        assert_eq!(sprt.get_result(&score(850, 950, 1200)), Some(SprtResult::AcceptH0));
+        assert_eq!(sprt.get_result(&score(300, 280, 400)), None);
+    }
+}
@@ -0,0 +1,189 @@
+use shakmaty::{CastlingMode, Chess, Color, File, FromSetup, Piece, Rank, Role, Setup, Square};
+use shakmaty_syzygy::{AmbiguousWdl, Tablebase as SyzygyTables, Wdl as SyzygyWdl};
+
+use crate::game::{board::Board, fen, piece::{PieceType, Player}, position::Position};
+
+use super::evaluation::MATE_SCORE;
+
+// below the mate scores, so a tablebase win never looks like a mate in n
+pub const TABLEBASE_WIN_SCORE: i32 = MATE_SCORE - 2000;
+
+// the result for the side to move, wins and losses the fifty-move rule spoils count as draws
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Wdl {
+    fn from_syzygy(wdl: SyzygyWdl) -> Self {
+        match wdl {
+            SyzygyWdl::Win => Wdl::Win,
+            SyzygyWdl::Loss => Wdl::Loss,
+            _ => Wdl::Draw,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TablebaseProbe {
+    pub wdl: Wdl,
+    // plies until the next capture or pawn move, only with dtz tables
+    pub dtz: Option<i32>,
+}
+
+impl TablebaseProbe {
+    pub fn format(&self) -> String {
+        let moves = self.dtz.map(|dtz| (dtz.abs() + 1) / 2);
+
+        match (self.wdl, moves) {
+            (Wdl::Draw, _) => "Tablebase: Draw".into(),
+            (Wdl::Win, Some(moves)) => format!("Tablebase: Win in {} (DTZ)", moves),
+            (Wdl::Loss, Some(moves)) => format!("Tablebase: Loss in {} (DTZ)", moves),
+            (Wdl::Win, None) => "Tablebase: Win".into(),
+            (Wdl::Loss, None) => "Tablebase: Loss".into(),
+        }
+    }
+}
+
+pub fn count_pieces(board: &Board) -> usize {
+    board.state.iter().flatten().filter(|piece| piece.is_some()).count()
+}
+
+// the shakmaty position without a fen round trip, positions with castling rights are not in the tables
+fn to_position(board: &Board, player: Player, fifty_move_rule: u8) -> Option<Chess> {
+    if [Player::White, Player::Black].iter().any(|player| !fen::get_castling_rooks(board, *player).is_empty()) {
+        return None;
+    }
+
+    let get_color = |player: Player| if player == Player::White { Color::White } else { Color::Black };
+    let get_square = |position: Position| Square::from_coords(File::new(position.column as u32), Rank::new(7 - position.row as u32));
+
+    let mut setup = Setup::empty();
+
+    for column in 0..8 {
+        for row in 0..8 {
+            let Some(piece) = &board.state[column][row] else {
+                continue;
+            };
+
+            let role = match piece.get_piece_type() {
+                PieceType::Pawn => Role::Pawn,
+                PieceType::Knight => Role::Knight,
+                PieceType::Bishop => Role::Bishop,
+                PieceType::Rook => Role::Rook,
+                PieceType::Queen => Role::Queen,
+                PieceType::King => Role::King,
+            };
+
+            setup.board.set_piece_at(get_square(Position::new(column as i32, row as i32)), Piece { color: get_color(piece.get_color()), role });
+        }
+    }
+
+    setup.turn = get_color(player);
+    setup.ep_square = board.en_passant.filter(|position| fen::can_capture_en_passant(board, *position, player)).map(get_square);
+    setup.halfmoves = fifty_move_rule as u32;
+
+    Chess::from_setup(setup, CastlingMode::Standard).ok()
+}
+
+// syzygy wdl and dtz files from a local directory
+pub struct Tablebase {
+    tables: SyzygyTables<Chess>,
+}
+
+impl Tablebase {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let mut tables = SyzygyTables::new();
+
+        let count = tables.add_directory(path).map_err(|error| format!("could not read tablebases in {}: {}", path, error))?;
+
+        if count == 0 {
+            return Err(format!("no syzygy tables in {}", path));
+        }
+
+        Ok(Self { tables })
+    }
+
+    pub fn get_max_pieces(&self) -> usize {
+        self.tables.max_pieces()
+    }
+
+    pub fn covers(&self, board: &Board) -> bool {
+        count_pieces(board) <= self.get_max_pieces()
+    }
+
+    fn get_position(&self, board: &Board, player: Player, fifty_move_rule: u8) -> Option<Chess> {
+        if !self.covers(board) {
+            return None;
+        }
+
+        to_position(board, player, fifty_move_rule)
+    }
+
+    // only needs wdl tables, only right after a capture or pawn move, which is when search probes
+    pub fn probe_wdl(&self, board: &Board, player: Player) -> Option<Wdl> {
+        let position = self.get_position(board, player, 0)?;
+
+        self.tables.probe_wdl_after_zeroing(&position).ok().map(Wdl::from_syzygy)
+    }
+
+    pub fn probe(&self, board: &Board, player: Player, fifty_move_rule: u8) -> Option<TablebaseProbe> {
+        let position = self.get_position(board, player, fifty_move_rule)?;
+
+        let Ok(dtz) = self.tables.probe_dtz(&position) else {
+            return self.probe_wdl(board, player).map(|wdl| TablebaseProbe { wdl, dtz: None });
+        };
+
+        // rounded dtz values can leave the result open, the wdl tables decide then
+        let wdl = match AmbiguousWdl::from_dtz_and_halfmoves(dtz, fifty_move_rule as u32).unambiguous() {
+            Some(wdl) => Wdl::from_syzygy(wdl),
+            None => self.probe_wdl(board, player)?,
+        };
+
+        Some(TablebaseProbe { wdl, dtz: Some(dtz.ignore_rounding().0) })
+    }
+
+    // the move that keeps the best result under the fifty-move rule, in uci
+    pub fn get_best_move(&self, board: &Board, player: Player, fifty_move_rule: u8) -> Option<String> {
+        let position = self.get_position(board, player, fifty_move_rule)?;
+        let (best_move, _) = self.tables.best_move(&position).ok()??;
+
+        Some(best_move.to_uci(CastlingMode::Standard).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shakmaty::{fen::Fen, EnPassantMode};
+
+    use crate::game::fen;
+
+    use super::to_position;
+
+    fn convert(text: &str) -> Option<String> {
+        let position = fen::parse_fen(text).unwrap();
+        let converted = to_position(&position.board, position.current_player, position.fifty_move_rule)?;
+
+        Some(Fen::from_position(&converted, EnPassantMode::Legal).to_string())
+    }
+
+    #[test]
+    fn converts_boards_without_a_fen_round_trip() {
+        for text in [
+            "8/8/4k3/8/8/8/4KQ2/8 w - - 0 1",
+            "8/8/4k3/8/8/8/4KQ2/8 b - - 37 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        ] {
+            assert_eq!(convert(text).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn skips_positions_with_castling_rights() {
+        assert_eq!(convert("4k3/8/8/8/8/8/8/4K2R w K - 0 1"), None);
+        assert!(convert("4k3/8/8/8/8/8/8/4K2R w - - 0 1").is_some());
+    }
+}
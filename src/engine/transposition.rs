@@ -0,0 +1,174 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+pub const DEFAULT_HASH_SIZE: usize = 16;
+pub const MAX_HASH_SIZE: usize = 1024;
+
+const ENTRY_BYTES: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bound {
+    Exact,
+    // the score is at least this, the search failed high
+    Lower,
+    // the score is at most this, no move raised alpha
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TableEntry {
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<u16>,
+    generation: u8,
+}
+
+impl TableEntry {
+    // score 32 bits, depth 8, bound 2, generation 6, move 16
+    // the bound is never 0, so no entry packs to the 0 of an empty slot
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+
+        (self.score as u32 as u64)
+            | (self.depth as u64) << 32
+            | bound << 40
+            | ((self.generation & 63) as u64) << 42
+            | (self.best_move.map_or(0, |best_move| best_move as u64 + 1) & 0xffff) << 48
+    }
+
+    fn unpack(data: u64) -> Self {
+        let best_move = (data >> 48) as u16;
+
+        Self {
+            score: data as u32 as i32,
+            depth: (data >> 32) as u8,
+            bound: match (data >> 40) & 3 {
+                1 => Bound::Exact,
+                2 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            generation: ((data >> 42) & 63) as u8,
+            best_move: best_move.checked_sub(1),
+        }
+    }
+}
+
+// a fixed-size table shared by searches without locks, each slot stores the key xor the data
+// so a slot torn by two writers no longer matches its key and reads as empty
+pub struct TranspositionTable {
+    slots: Vec<[AtomicU64; 2]>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    // the size in megabytes
+    pub fn new(size: usize) -> Self {
+        let count = 1 << (size.clamp(1, MAX_HASH_SIZE) * 1024 * 1024 / ENTRY_BYTES).ilog2();
+
+        Self {
+            slots: (0..count).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    fn get_slot(&self, hash: u64) -> &[AtomicU64; 2] {
+        &self.slots[hash as usize & (self.slots.len() - 1)]
+    }
+
+    // entries of earlier searches are replaced first
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot[0].store(0, Ordering::Relaxed);
+            slot[1].store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<TableEntry> {
+        let slot = self.get_slot(hash);
+        let key = slot[0].load(Ordering::Relaxed);
+        let data = slot[1].load(Ordering::Relaxed);
+
+        (data != 0 && key ^ data == hash).then(|| TableEntry::unpack(data))
+    }
+
+    // a deeper entry of the current search is kept, older entries are always replaced
+    pub fn store(&self, hash: u64, depth: u8, score: i32, bound: Bound, best_move: Option<u16>) {
+        let slot = self.get_slot(hash);
+        let generation = self.generation.load(Ordering::Relaxed) & 63;
+
+        let old_key = slot[0].load(Ordering::Relaxed);
+        let old_data = slot[1].load(Ordering::Relaxed);
+
+        let mut best_move = best_move;
+
+        if old_data != 0 {
+            let old_entry = TableEntry::unpack(old_data);
+            let is_same_position = old_key ^ old_data == hash;
+
+            if old_entry.generation == generation && old_entry.depth > depth {
+                return;
+            }
+
+            // a fail low has no move of its own, keep the one found before
+            if is_same_position && best_move.is_none() {
+                best_move = old_entry.best_move;
+            }
+        }
+
+        let data = TableEntry { depth, score, bound, best_move, generation }.pack();
+
+        slot[0].store(hash ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_and_unpacks_entries() {
+        for (depth, score, bound, best_move, generation) in [
+            (0, 0, Bound::Exact, None, 0),
+            (12, -31_000, Bound::Lower, Some(0), 63),
+            (255, i32::MAX, Bound::Upper, Some(0x7fff), 17),
+        ] {
+            let data = TableEntry { depth, score, bound, best_move, generation }.pack();
+            let entry = TableEntry::unpack(data);
+
+            assert_ne!(data, 0);
+            assert_eq!((entry.depth, entry.score, entry.bound, entry.best_move, entry.generation), (depth, score, bound, best_move, generation));
+        }
+    }
+
+    #[test]
+    fn finds_an_entry_that_is_all_zero_but_the_bound() {
+        let table = TranspositionTable::new(1);
+
+        table.store(42, 0, 0, Bound::Exact, None);
+
+        assert!(table.probe(42).is_some_and(|entry| entry.depth == 0 && entry.score == 0 && entry.bound == Bound::Exact));
+        assert!(table.probe(43).is_none());
+    }
+
+    #[test]
+    fn rejects_torn_writes() {
+        let table = TranspositionTable::new(1);
+
+        table.store(42, 5, 100, Bound::Exact, Some(7));
+
+        // another writer got its data in but not its key
+        let slot = table.get_slot(42);
+        slot[1].store(TableEntry { depth: 9, score: -50, bound: Bound::Lower, best_move: None, generation: 0 }.pack(), Ordering::Relaxed);
+
+        assert!(table.probe(42).is_none());
+    }
+}
@@ -0,0 +1,185 @@
+use std::{io::{BufRead, BufReader, Write}, process::{Child, ChildStdin, Command, Stdio}, sync::mpsc::{self, Receiver, RecvTimeoutError}, thread, time::{Duration, Instant}};
+
+use super::analysis::Score;
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct UciInfo {
+    pub depth: u8,
+    pub multipv: usize,
+    pub nodes: u64,
+    // relative to the side to move
+    pub score: Score,
+    pub pv: Vec<String>,
+}
+
+pub fn parse_info(line: &str) -> Option<UciInfo> {
+    let mut tokens = line.split_whitespace();
+
+    if tokens.next() != Some("info") {
+        return None;
+    }
+
+    let mut depth = 0;
+    let mut multipv = 1;
+    let mut nodes = 0;
+    let mut score = None;
+    let mut pv = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "depth" => depth = tokens.next()?.parse().ok()?,
+            "multipv" => multipv = tokens.next()?.parse().ok()?,
+            "nodes" => nodes = tokens.next()?.parse().ok()?,
+            "score" => score = match (tokens.next()?, tokens.next()?.parse().ok()?) {
+                ("cp", centipawns) => Some(Score::Centipawns(centipawns)),
+                ("mate", moves) => Some(Score::Mate(moves)),
+                _ => None,
+            },
+            "pv" => {
+                pv = tokens.by_ref().map(String::from).collect();
+            },
+            _ => (),
+        }
+    }
+
+    if pv.is_empty() {
+        return None;
+    }
+
+    Some(UciInfo {
+        depth,
+        multipv,
+        nodes,
+        score: score?,
+        pv,
+    })
+}
+
+pub struct UciEngine {
+    pub name: String,
+    // the names of the options the engine announced
+    pub options: Vec<String>,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl UciEngine {
+    pub fn start(path: &str) -> Result<Self, String> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("could not start '{}': {}", path, error))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            name: path.into(),
+            options: Vec::new(),
+            child,
+            stdin,
+            lines,
+        };
+
+        engine.send("uci")?;
+
+        for line in engine.wait_for("uciok", STARTUP_TIMEOUT)? {
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.into();
+            }
+
+            if let Some(option) = line.strip_prefix("option name ") {
+                engine.options.push(option.split(" type ").next().unwrap_or(option).into());
+            }
+        }
+
+        engine.wait_until_ready()?;
+
+        Ok(engine)
+    }
+
+    pub fn send(&mut self, command: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|error| format!("engine closed: {}", error))
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.send(&format!("setoption name {} value {}", name, value))
+    }
+
+    pub fn has_option(&self, name: &str) -> bool {
+        self.options.iter().any(|option| option.eq_ignore_ascii_case(name))
+    }
+
+    // a single-threaded engine has no threads option
+    pub fn set_threads(&mut self, threads: usize) -> Result<(), String> {
+        if !self.has_option("Threads") {
+            return Ok(());
+        }
+
+        self.set_option("Threads", &threads.to_string())
+    }
+
+    pub fn wait_until_ready(&mut self) -> Result<(), String> {
+        self.send("isready")?;
+        self.wait_for("readyok", STARTUP_TIMEOUT)?;
+
+        Ok(())
+    }
+
+    // collects output lines until one starts with `prefix`, which is included last
+    pub fn wait_for(&mut self, prefix: &str, timeout: Duration) -> Result<Vec<String>, String> {
+        let deadline = Instant::now() + timeout;
+        let mut lines = Vec::new();
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            match self.lines.recv_timeout(remaining) {
+                Ok(line) => {
+                    let done = line.starts_with(prefix);
+                    lines.push(line);
+
+                    if done {
+                        return Ok(lines);
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => return Err(format!("engine did not answer '{}'", prefix)),
+                Err(RecvTimeoutError::Disconnected) => return Err("engine closed".into()),
+            }
+        }
+    }
+
+    pub fn try_read_line(&mut self) -> Option<String> {
+        self.lines.try_recv().ok()
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+
+        thread::sleep(Duration::from_millis(50));
+
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,108 @@
+use super::{board::Board, piece::{PieceType, Player}, piece_move::ChessMove, position::Position};
+
+#[derive(Clone)]
+pub enum AnimationKind {
+    Slide(Position),
+    FadeOut,
+    Morph(PieceType),
+}
+
+#[derive(Clone)]
+pub struct AnimatedPiece {
+    pub player: Player,
+    pub piece_type: PieceType,
+    pub position: Position,
+    pub kind: AnimationKind,
+}
+
+#[derive(Clone)]
+pub struct Animation {
+    pub pieces: Vec<AnimatedPiece>,
+    pub hidden_positions: Vec<Position>,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+impl Animation {
+    pub fn from_move(board_before: &Board, piece_move: &dyn ChessMove, duration: f32) -> Self {
+        let from_pos = piece_move.get_from_pos();
+        let to_pos = piece_move.get_king_to();
+        let piece = board_before[from_pos].as_ref().unwrap();
+
+        let mut pieces = vec![AnimatedPiece {
+            player: piece.get_color(),
+            piece_type: piece.get_piece_type(),
+            position: from_pos,
+            kind: AnimationKind::Slide(to_pos),
+        }];
+        let mut hidden_positions = vec![to_pos];
+
+        let captured_pos = if piece_move.is_castle_move() {
+            None
+        } else if board_before[to_pos].is_some() {
+            Some(to_pos)
+        } else if piece.get_piece_type() == PieceType::Pawn && from_pos.column != to_pos.column {
+            Some(Position::new(to_pos.column, from_pos.row))
+        } else {
+            None
+        };
+
+        if let Some(captured_pos) = captured_pos {
+            if let Some(captured) = &board_before[captured_pos] {
+                pieces.push(AnimatedPiece {
+                    player: captured.get_color(),
+                    piece_type: captured.get_piece_type(),
+                    position: captured_pos,
+                    kind: AnimationKind::FadeOut,
+                });
+            }
+        }
+
+        if let Some((rook_from, rook_to)) = piece_move.get_castle_rook() {
+            if let Some(rook) = &board_before[rook_from] {
+                pieces.push(AnimatedPiece {
+                    player: rook.get_color(),
+                    piece_type: rook.get_piece_type(),
+                    position: rook_from,
+                    kind: AnimationKind::Slide(rook_to),
+                });
+                hidden_positions.push(rook_to);
+            }
+        }
+
+        Self {
+            pieces,
+            hidden_positions,
+            elapsed: 0.,
+            duration,
+        }
+    }
+
+    pub fn from_promotion(position: Position, player: Player, piece_type: PieceType, duration: f32) -> Self {
+        Self {
+            pieces: vec![AnimatedPiece {
+                player,
+                piece_type: PieceType::Pawn,
+                position,
+                kind: AnimationKind::Morph(piece_type),
+            }],
+            hidden_positions: vec![position],
+            elapsed: 0.,
+            duration,
+        }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.elapsed += delta;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    pub fn get_progress(&self) -> f32 {
+        let t = (self.elapsed / self.duration).clamp(0., 1.);
+
+        t * t * (3. - 2. * t)
+    }
+}
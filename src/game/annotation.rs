@@ -0,0 +1,66 @@
+use ggez::{graphics::Color, input::keyboard::KeyMods};
+
+use super::position::Position;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AnnotationColor {
+    Green,
+    Red,
+    Blue,
+    Yellow,
+}
+
+impl AnnotationColor {
+    pub fn from_mods(mods: KeyMods) -> Self {
+        let shift = mods.contains(KeyMods::SHIFT);
+        let ctrl = mods.contains(KeyMods::CTRL) || mods.contains(KeyMods::ALT);
+
+        match (shift, ctrl) {
+            (true, true) => AnnotationColor::Yellow,
+            (true, false) => AnnotationColor::Red,
+            (false, true) => AnnotationColor::Blue,
+            (false, false) => AnnotationColor::Green,
+        }
+    }
+
+    pub fn get_color(&self) -> Color {
+        match self {
+            AnnotationColor::Green => Color::from_rgba(21, 120, 27, 180),
+            AnnotationColor::Red => Color::from_rgba(136, 32, 32, 180),
+            AnnotationColor::Blue => Color::from_rgba(0, 48, 136, 180),
+            AnnotationColor::Yellow => Color::from_rgba(230, 143, 0, 180),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Annotations {
+    pub arrows: Vec<(Position, Position, AnnotationColor)>,
+    pub marks: Vec<(Position, AnnotationColor)>,
+}
+
+impl Annotations {
+    pub fn toggle_arrow(&mut self, from_pos: Position, to_pos: Position, color: AnnotationColor) {
+        let existing = self.arrows.iter().position(|(from, to, _)| *from == from_pos && *to == to_pos);
+
+        match existing {
+            Some(index) if self.arrows[index].2 == color => {
+                self.arrows.remove(index);
+            },
+            Some(index) => self.arrows[index].2 = color,
+            None => self.arrows.push((from_pos, to_pos, color)),
+        }
+    }
+
+    pub fn toggle_mark(&mut self, position: Position, color: AnnotationColor) {
+        let existing = self.marks.iter().position(|(pos, _)| *pos == position);
+
+        match existing {
+            Some(index) if self.marks[index].1 == color => {
+                self.marks.remove(index);
+            },
+            Some(index) => self.marks[index].1 = color,
+            None => self.marks.push((position, color)),
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use std::ops::{Index, IndexMut};
+use std::{collections::HashMap, ops::{Index, IndexMut}};
 
-use super::{piece::{bishop::Bishop, king::King, knight::Knight, pawn::Pawn, queen::Queen, ChessPiece, PieceType, Player}, position::Position};
+use super::{direction::Direction, piece_counting::PieceCounting, piece_move::ChessMove, piece::{bishop::Bishop, king::King, knight::Knight, pawn::Pawn, queen::Queen, ChessPiece, PieceType, Player}, position::Position};
 
 use crate::game::piece::rook::Rook;
 
@@ -82,7 +82,23 @@ impl Board {
                     continue;
                 }
 
-                for piece_move in piece.get_moves(Position::new(col as i32, row as i32), self) {
+                let from_position = Position::new(col as i32, row as i32);
+
+                if piece.get_piece_type() == PieceType::King {
+                    let attacks_king = Direction::get_all().into_iter().any(|direction| {
+                        let pos = from_position + direction.into();
+
+                        Board::is_inside(pos) && self[pos].as_ref().is_some_and(|other| other.get_piece_type() == PieceType::King && other.get_color() == player)
+                    });
+
+                    if attacks_king {
+                        return true;
+                    }
+
+                    continue;
+                }
+
+                for piece_move in piece.get_moves(from_position, self) {
                     if let Some(piece) = &self[piece_move.get_to_pos()] {
                         if piece.get_piece_type() == PieceType::King {
                             return true;
@@ -95,7 +111,180 @@ impl Board {
         false
     }
 
-    
+    pub fn get_legal_moves_from(&self, position: Position) -> Vec<Box<dyn ChessMove>> {
+        let Some(piece) = &self[position] else {
+            return Vec::new();
+        };
+
+        let player = piece.get_color();
+        let mut piece_moves = piece.get_moves(position, self);
+
+        piece_moves.retain(|piece_move| {
+            let mut test_board = self.clone();
+
+            piece_move.execute(&mut test_board);
+
+            !test_board.get_check(player)
+        });
+
+        piece_moves
+    }
+
+    pub fn find_move(&self, player: Player, from_pos: Position, to_pos: Position) -> Option<Box<dyn ChessMove>> {
+        if !self[from_pos].as_ref().is_some_and(|piece| piece.get_color() == player) {
+            return None;
+        }
+
+        self.get_legal_moves_from(from_pos)
+            .into_iter()
+            .find(|piece_move| piece_move.get_to_pos() == to_pos
+                || piece_move.get_castle_rook().is_some_and(|(rook_from, _)| rook_from == to_pos))
+    }
+
+    pub fn get_legal_moves(&self, player: Player) -> Vec<Box<dyn ChessMove>> {
+        let mut legal_moves = Vec::new();
+
+        for (col, table) in self.state.iter().enumerate() {
+            for (row, piece) in table.iter().enumerate() {
+                if let Some(piece) = piece {
+                    if piece.get_color() == player {
+                        legal_moves.append(&mut self.get_legal_moves_from(Position::new(col as i32, row as i32)));
+                    }
+                }
+            }
+        }
+
+        legal_moves
+    }
+
+    pub fn get_attacked_squares(&self, player: Player) -> Vec<Position> {
+        let mut attacked_squares = Vec::new();
+
+        for (col, table) in self.state.iter().enumerate() {
+            for (row, piece) in table.iter().enumerate() {
+                if let Some(piece) = piece {
+                    if piece.get_color() != player {
+                        continue;
+                    }
+
+                    let from_position = Position::new(col as i32, row as i32);
+
+                    if piece.get_piece_type() == PieceType::Pawn {
+                        let direction = if player == Player::White { Direction::NORTH } else { Direction::SOUTH };
+
+                        for side in [Direction::WEST, Direction::EAST] {
+                            let pos = from_position + (direction + side).into();
+
+                            if Board::is_inside(pos) && !attacked_squares.contains(&pos) {
+                                attacked_squares.push(pos);
+                            }
+                        }
+
+                        continue;
+                    }
+
+                    for piece_move in piece.get_moves(from_position, self) {
+                        if piece_move.is_castle_move() {
+                            continue;
+                        }
+
+                        if !attacked_squares.contains(&piece_move.get_to_pos()) {
+                            attacked_squares.push(piece_move.get_to_pos());
+                        }
+                    }
+                }
+            }
+        }
+
+        attacked_squares
+    }
+
+    pub fn get_total_pieces(&self) -> PieceCounting {
+        let mut total_pieces = 0;
+        let mut black_pieces = HashMap::new();
+        let mut white_pieces = HashMap::new();
+
+        for col in self.state.iter() {
+            for piece in col.iter().flatten() {
+                if piece.get_color() == Player::Black {
+                    black_pieces.insert(piece.get_piece_type(), black_pieces.get(&piece.get_piece_type()).unwrap_or(&0) + 1);
+                }
+                else {
+                    white_pieces.insert(piece.get_piece_type(), white_pieces.get(&piece.get_piece_type()).unwrap_or(&0) + 1);
+                }
+
+                total_pieces += 1;
+            }
+        }
+
+        PieceCounting {
+            total_pieces,
+            black_pieces,
+            white_pieces
+        }
+    }
+
+    pub fn promote(&mut self, position: Position, piece_type: PieceType) {
+        let player = self[position].as_ref().unwrap().get_color();
+
+        self[position] = match piece_type {
+            PieceType::Pawn | PieceType::King => None,
+            _ => Some(Board::new_piece(player, piece_type)),
+        };
+    }
+
+    pub fn new_piece(player: Player, piece_type: PieceType) -> Box<dyn ChessPiece> {
+        match piece_type {
+            PieceType::Pawn => Box::new(Pawn::new(player)),
+            PieceType::Knight => Box::new(Knight::new(player)),
+            PieceType::Bishop => Box::new(Bishop::new(player)),
+            PieceType::Rook => Box::new(Rook::new(player)),
+            PieceType::Queen => Box::new(Queen::new(player)),
+            PieceType::King => Box::new(King::new(player)),
+        }
+    }
+
+    pub fn chess960(number: u32) -> Self {
+        let mut back_rank: [Option<PieceType>; 8] = [None; 8];
+        let mut n = number % 960;
+
+        back_rank[(n % 4 * 2 + 1) as usize] = Some(PieceType::Bishop);
+        n /= 4;
+        back_rank[(n % 4 * 2) as usize] = Some(PieceType::Bishop);
+        n /= 4;
+
+        let queen_index = n % 6;
+        n /= 6;
+
+        let knight_indices = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][n as usize];
+
+        let mut place = |index: u32, piece_type: PieceType| {
+            let column = (0..8).filter(|column| back_rank[*column].is_none()).nth(index as usize).unwrap();
+
+            back_rank[column] = Some(piece_type);
+        };
+
+        place(queen_index, PieceType::Queen);
+        place(knight_indices.1, PieceType::Knight);
+        place(knight_indices.0, PieceType::Knight);
+        place(0, PieceType::Rook);
+        place(0, PieceType::King);
+        place(0, PieceType::Rook);
+
+        let mut board: Board = Self::default();
+
+        for (column, piece_type) in back_rank.into_iter().enumerate() {
+            let piece_type = piece_type.unwrap();
+
+            board.state[column][0] = Some(Board::new_piece(Player::Black, piece_type));
+            board.state[column][7] = Some(Board::new_piece(Player::White, piece_type));
+            board.state[column][1] = Some(Box::new(Pawn::new(Player::Black)));
+            board.state[column][6] = Some(Box::new(Pawn::new(Player::White)));
+        }
+
+        board
+    }
+
     pub fn find_piece(&self, player: Player, piece_type: PieceType) -> Position {
         for (x, col) in self.state.iter().enumerate() {
             for (y, piece) in col.into_iter().enumerate() {
@@ -139,74 +328,39 @@ impl Board {
 
         
 
-        snapshot += 'letter: {
-            if let Some(piece) = &self[Position::new(4, 7)] {
-                if piece.get_piece_type() == PieceType::King 
-                && !piece.get_has_moved() {
-                    if let Some(rook) = &self[Position::new(7, 7)] {
-                        if rook.get_piece_type() == PieceType::Rook
-                        && !rook.get_has_moved() {
-                            break 'letter "K";
-                        }
-                    }
-                    
-                }
-            };
+        for (player, row) in [(Player::White, 7), (Player::Black, 0)] {
+            let (mut king_side, mut queen_side) = (false, false);
 
-            "e"
-        };
+            for king_column in 0..8 {
+                let Some(king) = &self[Position::new(king_column, row)] else {
+                    continue;
+                };
 
-        snapshot += 'letter: {
-            if let Some(piece) = &self[Position::new(4, 7)] {
-                if piece.get_piece_type() == PieceType::King 
-                && !piece.get_has_moved() {
-                    if let Some(rook) = &self[Position::new(0, 7)] {
-                        if rook.get_piece_type() == PieceType::Rook
-                        && !rook.get_has_moved() {
-                            break 'letter "Q";
-                        }
-                    }
-                    
+                if king.get_piece_type() != PieceType::King || king.get_color() != player || king.get_has_moved() {
+                    continue;
                 }
-            };
-
-            "e"
-        };
-        
 
-        snapshot += 'letter: {
-            if let Some(piece) = &self[Position::new(4, 0)] {
-                if piece.get_piece_type() == PieceType::King 
-                && !piece.get_has_moved() {
-                    if let Some(rook) = &self[Position::new(7, 0)] {
+                for rook_column in 0..8 {
+                    if let Some(rook) = &self[Position::new(rook_column, row)] {
                         if rook.get_piece_type() == PieceType::Rook
+                        && rook.get_color() == player
                         && !rook.get_has_moved() {
-                            break 'letter "k";
+                            if rook_column > king_column {
+                                king_side = true;
+                            }
+                            else {
+                                queen_side = true;
+                            }
                         }
                     }
-                    
                 }
-            };
-
-            "e"
-        };
+            }
 
-        snapshot += 'letter: {
-            if let Some(piece) = &self[Position::new(4, 0)] {
-                if piece.get_piece_type() == PieceType::King 
-                && !piece.get_has_moved() {
-                    if let Some(rook) = &self[Position::new(0, 0)] {
-                        if rook.get_piece_type() == PieceType::Rook
-                        && !rook.get_has_moved() {
-                            break 'letter "q";
-                        }
-                    }
-                    
-                }
-            };
+            let (king_letter, queen_letter) = if player == Player::White { ("K", "Q") } else { ("k", "q") };
 
-            "e"
-        };
+            snapshot += if king_side { king_letter } else { "e" };
+            snapshot += if queen_side { queen_letter } else { "e" };
+        }
 
         // let white_king_pos = self.find_piece(Player::White, PieceType::King);
         // let white_king_moves = self[white_king_pos].clone().unwrap().get_moves(white_king_pos, self);
@@ -254,8 +408,6 @@ impl Board {
 
 
 
-        println!("{}", snapshot);
-
         snapshot
     }
 }
@@ -273,3 +425,46 @@ impl IndexMut<Position> for Board {
         &mut self.state[index.column as usize][index.row as usize]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::game::fen;
+
+    use super::*;
+
+    fn get_back_rank(board: &Board) -> String {
+        fen::get_fen(board, Player::White, 0, 1).split(['/', ' ']).nth(7).unwrap().into()
+    }
+
+    #[test]
+    fn chess960_518_is_the_standard_position() {
+        assert_eq!(fen::get_fen(&Board::chess960(518), Player::White, 0, 1), fen::get_fen(&Board::new(), Player::White, 0, 1));
+        assert_eq!(get_back_rank(&Board::chess960(518)), "RNBQKBNR");
+    }
+
+    #[test]
+    fn chess960_positions_are_valid_and_distinct() {
+        let mut back_ranks = HashSet::new();
+
+        for number in 0..960 {
+            let back_rank: Vec<char> = get_back_rank(&Board::chess960(number)).chars().collect();
+            let columns = |piece: char| back_rank.iter().enumerate().filter(|(_, letter)| **letter == piece).map(|(column, _)| column).collect::<Vec<_>>();
+
+            let rooks = columns('R');
+            let king = columns('K');
+            let bishops = columns('B');
+
+            assert_eq!(back_rank.len(), 8, "position {}", number);
+            assert!(rooks[0] < king[0] && king[0] < rooks[1], "position {}", number);
+            assert_ne!(bishops[0] % 2, bishops[1] % 2, "position {}", number);
+            assert_eq!(columns('Q').len(), 1, "position {}", number);
+            assert_eq!(columns('N').len(), 2, "position {}", number);
+
+            back_ranks.insert(back_rank);
+        }
+
+        assert_eq!(back_ranks.len(), 960);
+    }
+}
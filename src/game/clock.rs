@@ -0,0 +1,67 @@
+use crate::settings::TimeControl;
+
+use super::piece::Player;
+
+const LOW_TIME: f32 = 10.;
+
+#[derive(Clone)]
+pub struct Clock {
+    pub remaining: [f32; 2],
+    pub increment: f32,
+    pub low_time_warned: [bool; 2],
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl) -> Self {
+        let total = time_control.minutes as f32 * 60.;
+
+        Self {
+            remaining: [total, total],
+            increment: time_control.increment as f32,
+            low_time_warned: [false, false],
+        }
+    }
+
+    fn get_index(player: Player) -> usize {
+        if player == Player::White { 0 } else { 1 }
+    }
+
+    pub fn get_remaining(&self, player: Player) -> f32 {
+        self.remaining[Self::get_index(player)]
+    }
+
+    // returns true the first time the player drops below the low time threshold
+    pub fn tick(&mut self, player: Player, delta: f32) -> bool {
+        let index = Self::get_index(player);
+
+        self.remaining[index] = (self.remaining[index] - delta).max(0.);
+
+        if self.remaining[index] < LOW_TIME && !self.low_time_warned[index] {
+            self.low_time_warned[index] = true;
+
+            return true;
+        }
+
+        false
+    }
+
+    pub fn add_increment(&mut self, player: Player) {
+        self.remaining[Self::get_index(player)] += self.increment;
+    }
+
+    pub fn is_flagged(&self, player: Player) -> bool {
+        self.get_remaining(player) <= 0.
+    }
+
+    pub fn format(&self, player: Player) -> String {
+        let remaining = self.get_remaining(player);
+
+        if remaining < LOW_TIME {
+            return format!("{:.1}", remaining);
+        }
+
+        let seconds = remaining.ceil() as u32;
+
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -17,6 +17,12 @@ impl Direction {
     }
 }
 
+impl Direction {
+    pub fn get_all() -> [Self; 8] {
+        [Self::NORTH, Self::NORTH_WEST, Self::WEST, Self::SOUTH_WEST, Self::SOUTH, Self::SOUTH_EAST, Self::EAST, Self::NORTH_EAST]
+    }
+}
+
 impl Into<Position> for Direction {
     fn into(self) -> Position {
         Position::new(self.column_delta, self.row_delta)
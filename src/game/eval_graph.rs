@@ -0,0 +1,112 @@
+use ggez::{glam::Vec2, graphics::{self, Canvas, Color, Rect}, Context};
+
+use crate::engine::review::{GameReview, MoveClass};
+
+use super::game_event::GameEvent;
+
+const GRAPH_X: f32 = 292.;
+const GRAPH_Y: f32 = 30.;
+const GRAPH_WIDTH: f32 = 152.;
+const GRAPH_HEIGHT: f32 = 96.;
+
+#[derive(Default)]
+pub struct EvalGraph {
+    pub is_visible: bool,
+}
+
+impl EvalGraph {
+    fn get_x(evaluations: &GameReview, ply: usize) -> f32 {
+        let last_ply = evaluations.evals.len().saturating_sub(1).max(1);
+
+        GRAPH_X + GRAPH_WIDTH * ply as f32 / last_ply as f32
+    }
+
+    fn get_point(evaluations: &GameReview, ply: usize) -> Option<Vec2> {
+        let eval = evaluations.evals.get(ply)?.as_ref()?;
+
+        Some(Vec2::new(
+            Self::get_x(evaluations, ply),
+            GRAPH_Y + GRAPH_HEIGHT * (1. - eval.score.get_win_percent() / 100.)
+        ))
+    }
+
+    pub fn draw(&self, evaluations: &GameReview, displayed_ply: usize, canvas: &mut Canvas, ctx: &mut Context) {
+        canvas.draw(&graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., 0., GRAPH_WIDTH, GRAPH_HEIGHT),
+            Color::from_rgb(64, 64, 64)
+        ).unwrap(), [GRAPH_X, GRAPH_Y]);
+
+        let points: Vec<Vec2> = (0..evaluations.evals.len())
+            .map_while(|ply| Self::get_point(evaluations, ply))
+            .collect();
+
+        // fill white's share of the graph beneath the curve
+        for pair in points.windows(2) {
+            let bottom = GRAPH_Y + GRAPH_HEIGHT;
+
+            // an empty area would make an empty mesh
+            if pair[0].y >= bottom - 0.5 && pair[1].y >= bottom - 0.5 {
+                continue;
+            }
+
+            canvas.draw(&graphics::Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::fill(),
+                &[pair[0], pair[1], Vec2::new(pair[1].x, bottom), Vec2::new(pair[0].x, bottom)],
+                Color::from_rgb(240, 240, 240)
+            ).unwrap(), [0., 0.]);
+        }
+
+        canvas.draw(&graphics::Mesh::new_line(
+            ctx,
+            &[Vec2::new(GRAPH_X, GRAPH_Y + GRAPH_HEIGHT / 2.), Vec2::new(GRAPH_X + GRAPH_WIDTH, GRAPH_Y + GRAPH_HEIGHT / 2.)],
+            1.,
+            Color::from_rgb(128, 128, 128)
+        ).unwrap(), [0., 0.]);
+
+        let x = Self::get_x(evaluations, displayed_ply);
+
+        canvas.draw(&graphics::Mesh::new_line(
+            ctx,
+            &[Vec2::new(x, GRAPH_Y), Vec2::new(x, GRAPH_Y + GRAPH_HEIGHT)],
+            1.,
+            Color::from_rgb(30, 110, 230)
+        ).unwrap(), [0., 0.]);
+
+        for (ply, point) in points.iter().enumerate() {
+            let color = match evaluations.get_reviewed_move(ply).map(|reviewed_move| reviewed_move.class) {
+                Some(MoveClass::Blunder) => Color::RED,
+                Some(MoveClass::Mistake) => Color::from_rgb(255, 140, 0),
+                _ => continue,
+            };
+
+            canvas.draw(&graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::fill(),
+                *point,
+                3.,
+                0.5,
+                color
+            ).unwrap(), [0., 0.]);
+        }
+    }
+
+    pub fn check_for_message(&self, press_position: Option<Vec2>, evaluations: &GameReview) -> Option<GameEvent> {
+        let position = press_position?;
+
+        if position.x < GRAPH_X || position.x > GRAPH_X + GRAPH_WIDTH
+        || position.y < GRAPH_Y || position.y > GRAPH_Y + GRAPH_HEIGHT {
+            return None;
+        }
+
+        let last_ply = evaluations.evals.len().saturating_sub(1);
+        let ply = ((position.x - GRAPH_X) / GRAPH_WIDTH * last_ply as f32).round() as usize;
+
+        match ply.min(last_ply) {
+            0 => Some(GameEvent::ShowFirstMove),
+            ply => Some(GameEvent::ShowMove(ply - 1)),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use super::{board::Board, piece::{PieceType, Player}, position::Position};
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Clone)]
+pub struct FenPosition {
+    pub board: Board,
+    pub current_player: Player,
+    pub fifty_move_rule: u8,
+    pub fullmove_number: u32,
+}
+
+pub fn parse_fen(fen: &str) -> Result<FenPosition, String> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+
+    if fields.len() < 2 {
+        return Err("FEN needs at least a board and a side to move".into());
+    }
+
+    let mut board = Board::default();
+    let rows: Vec<&str> = fields[0].split('/').collect();
+
+    if rows.len() != 8 {
+        return Err(format!("expected 8 ranks, found {}", rows.len()));
+    }
+
+    for (row, rank) in rows.iter().enumerate() {
+        let mut column = 0;
+
+        for letter in rank.chars() {
+            if let Some(empty) = letter.to_digit(10) {
+                column += empty as i32;
+                continue;
+            }
+
+            let piece_type = PieceType::from_letter(letter).ok_or(format!("unknown piece '{}'", letter))?;
+            let player = if letter.is_ascii_uppercase() { Player::White } else { Player::Black };
+
+            if column >= 8 {
+                return Err(format!("rank {} is too long", 8 - row));
+            }
+
+            let mut piece = Board::new_piece(player, piece_type);
+            let start_row = if player == Player::White { 6 } else { 1 };
+
+            if piece_type != PieceType::Pawn || row != start_row {
+                piece.set_has_moved();
+            }
+
+            board.state[column as usize][row] = Some(piece);
+            column += 1;
+        }
+
+        if column != 8 {
+            return Err(format!("rank {} does not have 8 squares", 8 - row));
+        }
+    }
+
+    let current_player = match fields[1] {
+        "w" => Player::White,
+        "b" => Player::Black,
+        other => return Err(format!("unknown side to move '{}'", other)),
+    };
+
+    let castling = fields.get(2).copied().unwrap_or("-");
+
+    if castling != "-" {
+        for letter in castling.chars() {
+            set_castling_right(&mut board, letter)?;
+        }
+    }
+
+    let en_passant = fields.get(3).copied().unwrap_or("-");
+
+    if en_passant != "-" {
+        let position = Position::from_algebraic(en_passant).ok_or(format!("invalid en passant square '{}'", en_passant))?;
+        let expected_row = if current_player == Player::White { 2 } else { 5 };
+
+        if position.row != expected_row {
+            return Err(format!("invalid en passant square '{}'", en_passant));
+        }
+
+        board.en_passant = Some(position);
+    }
+
+    let fifty_move_rule = fields.get(4).map(|field| field.parse::<u8>()).unwrap_or(Ok(0)).map_err(|_| "invalid halfmove clock")?;
+    let fullmove_number = fields.get(5).map(|field| field.parse::<u32>()).unwrap_or(Ok(1)).map_err(|_| "invalid fullmove number")?;
+
+    validate(&board, current_player)?;
+
+    Ok(FenPosition {
+        board,
+        current_player,
+        fifty_move_rule,
+        fullmove_number: fullmove_number.max(1),
+    })
+}
+
+fn set_castling_right(board: &mut Board, letter: char) -> Result<(), String> {
+    let player = if letter.is_ascii_uppercase() { Player::White } else { Player::Black };
+    let row = if player == Player::White { 7 } else { 0 };
+
+    let king_column = (0..8)
+        .find(|column| board[Position::new(*column, row)].as_ref()
+            .is_some_and(|piece| piece.get_piece_type() == PieceType::King && piece.get_color() == player))
+        .ok_or(format!("castling right '{}' without a king on its back rank", letter))?;
+
+    let is_rook = |board: &Board, column: i32| board[Position::new(column, row)].as_ref()
+        .is_some_and(|piece| piece.get_piece_type() == PieceType::Rook && piece.get_color() == player);
+
+    let rook_column = match letter.to_ascii_uppercase() {
+        'K' => (king_column + 1..8).rev().find(|column| is_rook(board, *column)),
+        'Q' => (0..king_column).find(|column| is_rook(board, *column)),
+        file @ 'A'..='H' => Some(file as i32 - 'A' as i32).filter(|column| is_rook(board, *column)),
+        _ => return Err(format!("unknown castling right '{}'", letter)),
+    }.ok_or(format!("castling right '{}' without a matching rook", letter))?;
+
+    reset_has_moved(board, Position::new(king_column, row));
+    reset_has_moved(board, Position::new(rook_column, row));
+
+    Ok(())
+}
+
+fn reset_has_moved(board: &mut Board, position: Position) {
+    let piece = board[position].as_ref().unwrap();
+
+    board[position] = Some(Board::new_piece(piece.get_color(), piece.get_piece_type()));
+}
+
+pub fn validate(board: &Board, current_player: Player) -> Result<(), String> {
+    let pieces = board.get_total_pieces();
+
+    for player in [Player::White, Player::Black] {
+        let kings = pieces.get_pieces(player).get(&PieceType::King).copied().unwrap_or(0);
+
+        if kings != 1 {
+            return Err(format!("{:?} must have exactly one king", player));
+        }
+    }
+
+    for column in 0..8 {
+        for row in [0, 7] {
+            if board.state[column][row].as_ref().is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn) {
+                return Err("pawns can't stand on the first or last rank".into());
+            }
+        }
+    }
+
+    if board.get_check(current_player.get_opponent()) {
+        return Err(format!("{:?} is in check but it is not their turn", current_player.get_opponent()));
+    }
+
+    Ok(())
+}
+
+pub fn get_placement(board: &Board) -> String {
+    let mut rows = Vec::new();
+
+    for row in 0..8 {
+        let mut rank = String::new();
+        let mut empty = 0;
+
+        for column in 0..8 {
+            match &board.state[column][row] {
+                Some(piece) => {
+                    if empty > 0 {
+                        rank += &empty.to_string();
+                        empty = 0;
+                    }
+
+                    let letter = piece.get_piece_type().get_letter();
+
+                    rank.push(if piece.get_color() == Player::White { letter } else { letter.to_ascii_lowercase() });
+                },
+                None => empty += 1,
+            }
+        }
+
+        if empty > 0 {
+            rank += &empty.to_string();
+        }
+
+        rows.push(rank);
+    }
+
+    rows.join("/")
+}
+
+pub fn get_fen(board: &Board, current_player: Player, fifty_move_rule: u8, fullmove_number: u32) -> String {
+    let mut castling = String::new();
+
+    for player in [Player::White, Player::Black] {
+        for (rook_column, is_outermost, is_king_side) in get_castling_rooks(board, player) {
+            let letter = match (is_outermost, is_king_side) {
+                (true, true) => 'K',
+                (true, false) => 'Q',
+                _ => (b'A' + rook_column as u8) as char,
+            };
+
+            castling.push(if player == Player::White { letter } else { letter.to_ascii_lowercase() });
+        }
+    }
+
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    let en_passant = board.en_passant
+        .filter(|position| can_capture_en_passant(board, *position, current_player))
+        .map(|position| position.to_algebraic())
+        .unwrap_or("-".into());
+
+    format!(
+        "{} {} {} {} {} {}",
+        get_placement(board),
+        if current_player == Player::White { "w" } else { "b" },
+        castling,
+        en_passant,
+        fifty_move_rule,
+        fullmove_number
+    )
+}
+
+// the rooks the player can still castle with: column, outermost on its side, king side
+pub fn get_castling_rooks(board: &Board, player: Player) -> Vec<(i32, bool, bool)> {
+    let row = if player == Player::White { 7 } else { 0 };
+
+    let Some(king_column) = (0..8).find(|column| board[Position::new(*column, row)].as_ref()
+        .is_some_and(|piece| piece.get_piece_type() == PieceType::King && piece.get_color() == player && !piece.get_has_moved())) else {
+        return Vec::new();
+    };
+
+    let rook_columns: Vec<i32> = (0..8)
+        .filter(|column| board[Position::new(*column, row)].as_ref()
+            .is_some_and(|piece| piece.get_piece_type() == PieceType::Rook && piece.get_color() == player))
+        .collect();
+
+    let mut rooks = Vec::new();
+
+    for column in rook_columns.iter() {
+        if board[Position::new(*column, row)].as_ref().unwrap().get_has_moved() {
+            continue;
+        }
+
+        let is_king_side = *column > king_column;
+        let is_outermost = if is_king_side {
+            rook_columns.iter().all(|other| other <= column)
+        } else {
+            rook_columns.iter().all(|other| other >= column)
+        };
+
+        rooks.push((*column, is_outermost, is_king_side));
+    }
+
+    rooks.sort_by_key(|(_, _, is_king_side)| !is_king_side);
+
+    rooks
+}
+
+pub fn can_capture_en_passant(board: &Board, position: Position, current_player: Player) -> bool {
+    let pawn_row = if current_player == Player::White { position.row + 1 } else { position.row - 1 };
+
+    [position.column - 1, position.column + 1].into_iter().any(|column| {
+        let pawn_position = Position::new(column, pawn_row);
+
+        Board::is_inside(pawn_position) && board[pawn_position].as_ref()
+            .is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn && piece.get_color() == current_player)
+    })
+}
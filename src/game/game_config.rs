@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::TimeControl;
+
+use super::{board::Board, fen::{self, FenPosition}, piece::Player};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum Opponent {
+    Human,
+    Engine(u8),
+    External(String),
+    LanHost(u16),
+    LanJoin(String),
+}
+
+// who decides the moves of one side
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum PlayerKind {
+    Mouse,
+    Keyboard,
+    Engine(u8),
+    External(String),
+    Network,
+    Replay(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum AnalysisEngine {
+    BuiltIn,
+    External(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Variant {
+    Standard,
+    Chess960,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum StartPosition {
+    Standard,
+    Fen(String),
+    Chess960(u32),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct GameConfig {
+    pub opponent: Opponent,
+    pub player_color: Player,
+    pub time_control: Option<TimeControl>,
+    pub variant: Variant,
+    pub start_position: StartPosition,
+    pub analysis: Option<AnalysisEngine>,
+    // white and black, replaces the sides given by the opponent and colour
+    pub players: Option<[PlayerKind; 2]>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            opponent: Opponent::Human,
+            player_color: Player::White,
+            time_control: None,
+            variant: Variant::Standard,
+            start_position: StartPosition::Standard,
+            analysis: None,
+            players: None,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn get_start_position(&self) -> Result<FenPosition, String> {
+        match &self.start_position {
+            StartPosition::Standard => fen::parse_fen(fen::STARTING_FEN),
+            StartPosition::Fen(fen) => fen::parse_fen(fen),
+            StartPosition::Chess960(number) => {
+                if *number >= 960 {
+                    return Err(format!("Chess960 positions are numbered 0-959, not {}", number));
+                }
+
+                Ok(FenPosition {
+                    board: Board::chess960(*number),
+                    current_player: Player::White,
+                    fifty_move_rule: 0,
+                    fullmove_number: 1,
+                })
+            },
+        }
+    }
+
+    pub fn get_player_kinds(&self) -> [PlayerKind; 2] {
+        if let Some(players) = &self.players {
+            return players.clone();
+        }
+
+        let opponent = match &self.opponent {
+            _ if self.analysis.is_some() => PlayerKind::Mouse,
+            Opponent::Human => PlayerKind::Mouse,
+            Opponent::Engine(level) => PlayerKind::Engine(*level),
+            Opponent::External(path) => PlayerKind::External(path.clone()),
+            Opponent::LanHost(_) | Opponent::LanJoin(_) => PlayerKind::Network,
+        };
+
+        match self.player_color {
+            Player::White => [PlayerKind::Mouse, opponent],
+            Player::Black => [opponent, PlayerKind::Mouse],
+        }
+    }
+
+    pub fn get_human_players(&self) -> Vec<Player> {
+        let [white, black] = self.get_player_kinds();
+
+        [(Player::White, white), (Player::Black, black)].into_iter()
+            .filter(|(_, kind)| matches!(kind, PlayerKind::Mouse | PlayerKind::Keyboard))
+            .map(|(player, _)| player)
+            .collect()
+    }
+}
@@ -3,6 +3,18 @@ use super::{piece::PieceType, position::Position};
 pub enum GameEvent {
     Replay,
     Exit,
+    SaveGame,
+    Review,
+    CloseReview,
+    ToggleGraph,
+    OfferDraw,
+    Resign,
+    SendChat(String),
     ChoosePiece(PieceType, Position),
-    CheckForGameEnd
+    CheckForGameEnd,
+    ShowFirstMove,
+    ShowPreviousMove,
+    ShowNextMove,
+    ShowLastMove,
+    ShowMove(usize),
 }
\ No newline at end of file
@@ -0,0 +1,12 @@
+use super::{board::Board, piece::{PieceType, Player}, position::Position};
+
+#[derive(Clone)]
+pub struct HistoryEntry {
+    pub board: Board,
+    pub san: String,
+    pub from_pos: Position,
+    pub to_pos: Position,
+    pub promotion: Option<PieceType>,
+    pub current_player: Player,
+    pub is_checked: bool,
+}
@@ -1,15 +1,27 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use board::Board;
+use direction::Direction;
 use game_event::GameEvent;
-use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, Rect}, Context};
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, Drawable, Rect, Text}, input::keyboard::KeyCode, Context};
+use animation::{Animation, AnimationKind};
+use clock::Clock;
+use game_config::{AnalysisEngine, GameConfig, Opponent, PlayerKind, Variant};
+use annotation::{AnnotationColor, Annotations};
+use history::HistoryEntry;
 use modals::{endgame_modal::{EndType, EndgameModal}, pawn_promotion_modal::PawnPromotionModal, Modal};
-use piece::{bishop::Bishop, knight::Knight, queen::Queen, rook::Rook, PieceType, Player};
+use eval_graph::EvalGraph;
+use move_list::MoveList;
+use network_panel::NetworkPanel;
+use review_panel::ReviewPanel;
+use piece::{PieceType, Player};
 use piece_counting::PieceCounting;
-use piece_move::ChessMove;
+use piece_move::{normal_move::NormalMove, ChessMove};
+use player_controller::{ControllerKind, ControllerMove, MouseController, MoveRequest, PlayerController};
 use position::Position;
+use saved_game::SavedGame;
 
-use crate::{app::AppEvent, assets::Assets, scenes::Scene};
+use crate::{app::AppEvent, assets::Assets, engine::{analysis::{Analyser, ANALYSIS_LINES}, opening_book::OpeningBook, review::GameReview, tablebase::{Tablebase, TablebaseProbe}, EngineOptions}, network::{protocol::NetMessage, DrawOffer, NetSession}, scenes::Scene, settings::Settings, sounds::SoundEffect};
 
 pub mod board;
 pub mod piece;
@@ -19,6 +31,21 @@ pub mod direction;
 pub mod modals;
 pub mod game_event;
 pub mod piece_counting;
+pub mod notation;
+pub mod history;
+pub mod move_list;
+pub mod eval_graph;
+pub mod review_panel;
+pub mod network_panel;
+pub mod animation;
+pub mod annotation;
+pub mod saved_game;
+pub mod puzzle;
+pub mod fen;
+pub mod zobrist;
+pub mod clock;
+pub mod game_config;
+pub mod player_controller;
 
 pub struct Game {
     pub board: Board,
@@ -29,19 +56,797 @@ pub struct Game {
     pub snapshots: HashMap<String, u8>,
     pub is_checked: bool,
     pub game_events: Vec<GameEvent>,
+    pub selected_position: Option<Position>,
+    pub last_move: Option<(Position, Position)>,
+    pub show_threats: bool,
+    pub flipped: bool,
+    pub start_board: Board,
+    pub history: Vec<HistoryEntry>,
+    // the same moves in uci, for engines and saves
+    pub uci_moves: Vec<String>,
+    pub viewed_ply: Option<usize>,
+    pub pending_promotion: Option<(Board, Box<dyn ChessMove>)>,
+    pub move_list: MoveList,
+    pub animation: Option<Animation>,
+    pub animation_duration: f32,
+    pub pending_sound: Option<SoundEffect>,
+    pub annotations: HashMap<usize, Annotations>,
+    pub annotation_start: Option<Position>,
+    // white's and black's, asked for a move on their turn
+    pub controllers: [Box<dyn PlayerController>; 2],
+    pub premoves: Vec<Box<dyn ChessMove>>,
+    pub auto_queen: bool,
+    pub confirm_moves: bool,
+    pub pending_confirmation: Option<Box<dyn ChessMove>>,
+    pub config: GameConfig,
+    pub start_player: Player,
+    pub start_fen: String,
+    pub clock: Option<Clock>,
+    pub end_type: Option<EndType>,
+    pub analyser: Option<Analyser>,
+    pub analysed_fen: Option<String>,
+    pub analysis_san: Vec<Vec<String>>,
+    // the book and tablebase of the built-in engine, also shown in analysis
+    pub engine_options: EngineOptions,
+    // the san and weight of the book moves in the analysed position
+    pub book_moves: Vec<(String, u16)>,
+    pub tablebase_probe: Option<TablebaseProbe>,
+    pub preview: Option<(usize, Board)>,
+    pub review_panel: Option<ReviewPanel>,
+    pub evaluations: Option<GameReview>,
+    pub eval_graph: EvalGraph,
+    pub is_puzzle: bool,
+    pub network: Option<NetSession>,
+    pub network_panel: Option<NetworkPanel>,
+    // the file name or error of saving with s, shown until the next move
+    pub save_result: Option<Result<String, String>>,
 }
 
 impl Game {
-    pub fn new() -> Self {
-        Self {
-            board: Board::new(),
+    pub fn new(ctx: &mut Context, settings: &Settings, config: GameConfig) -> Result<Self, String> {
+        let start = config.get_start_position()?;
+
+        let analyser = match &config.analysis {
+            None => None,
+            Some(AnalysisEngine::BuiltIn) => Some(Analyser::built_in(settings.hash_size, settings.threads)),
+            Some(AnalysisEngine::External(path)) => Some(Analyser::external(path, config.variant == Variant::Chess960, settings.threads)?),
+        };
+
+        // polyglot books only cover standard chess
+        let book = match &settings.opening_book {
+            Some(path) if config.variant == Variant::Standard => match OpeningBook::load(path) {
+                Ok(book) => Some(Arc::new(book)),
+                Err(error) => {
+                    println!("{}", error);
+
+                    None
+                },
+            },
+            _ => None,
+        };
+
+        let tablebase = match &settings.tablebase_path {
+            Some(path) if config.variant == Variant::Standard => match Tablebase::open(path) {
+                Ok(tablebase) => Some(Arc::new(tablebase)),
+                Err(error) => {
+                    println!("{}", error);
+
+                    None
+                },
+            },
+            _ => None,
+        };
+
+        let engine_options = EngineOptions {
+            book,
+            tablebase,
+            hash_size: settings.hash_size,
+            threads: settings.threads,
+        };
+
+        let mut controllers = Vec::new();
+
+        for kind in config.get_player_kinds() {
+            let kind = match kind {
+                PlayerKind::Mouse if settings.keyboard_moves => PlayerKind::Keyboard,
+                kind => kind,
+            };
+
+            controllers.push(player_controller::new_controller(&kind, config.variant == Variant::Chess960, &engine_options)?);
+        }
+
+        let plays_black = config.get_human_players() == vec![Player::Black];
+
+        let start_fen = fen::get_fen(&start.board, start.current_player, start.fifty_move_rule, start.fullmove_number);
+
+        let mut game = Self {
+            board: start.board.clone(),
             modals: None,
             legal_moves: None,
-            current_player: Player::White,
-            fifty_move_rule: 0,
+            current_player: start.current_player,
+            fifty_move_rule: start.fifty_move_rule,
             snapshots: HashMap::new(),
             is_checked: false,
             game_events: Vec::new(),
+            selected_position: None,
+            last_move: None,
+            show_threats: settings.show_threats,
+            flipped: settings.flipped != plays_black,
+            start_board: start.board,
+            history: Vec::new(),
+            uci_moves: Vec::new(),
+            viewed_ply: None,
+            pending_promotion: None,
+            move_list: MoveList::new(ctx, start.current_player, start.fullmove_number),
+            animation: None,
+            animation_duration: settings.animation_speed.get_duration(),
+            pending_sound: None,
+            annotations: HashMap::new(),
+            annotation_start: None,
+            controllers: [controllers.remove(0), controllers.remove(0)],
+            premoves: Vec::new(),
+            auto_queen: settings.auto_queen,
+            confirm_moves: settings.confirm_moves,
+            pending_confirmation: None,
+            start_player: start.current_player,
+            start_fen,
+            clock: config.time_control.filter(|_| analyser.is_none()).map(Clock::new),
+            end_type: None,
+            analyser,
+            analysed_fen: None,
+            analysis_san: Vec::new(),
+            engine_options,
+            book_moves: Vec::new(),
+            tablebase_probe: None,
+            preview: None,
+            review_panel: None,
+            evaluations: None,
+            eval_graph: EvalGraph::default(),
+            is_puzzle: false,
+            network: None,
+            network_panel: None,
+            save_result: None,
+            config,
+        };
+
+        // the joining side gets its session from the join menu
+        if let Opponent::LanHost(port) = game.config.opponent {
+            game.attach_network(NetSession::host(port)?, ctx);
+        }
+
+        game.update_move_list_layout();
+
+        Ok(game)
+    }
+
+    pub fn attach_network(&mut self, network: NetSession, ctx: &mut Context) {
+        self.network = Some(network);
+        self.network_panel = Some(NetworkPanel::new(ctx));
+
+        self.update_move_list_layout();
+    }
+
+    pub fn from_saved_game(ctx: &mut Context, settings: &Settings, saved_game: &SavedGame) -> Result<Self, String> {
+        let mut game = Game::new(ctx, settings, saved_game.config.clone())?;
+        game.flipped = saved_game.flipped;
+
+        for (i, uci) in saved_game.moves.iter().enumerate() {
+            let (from_pos, to_pos, promotion) = notation::parse_uci(uci).ok_or(format!("invalid move '{}'", uci))?;
+
+            let piece_move = game.board.find_move(game.current_player, from_pos, to_pos).ok_or(format!("illegal move '{}'", uci))?;
+
+            if piece_move.is_pawn_promotion_move() && promotion.is_none() {
+                return Err(format!("missing promotion in '{}'", uci));
+            }
+
+            game.apply_move(piece_move, promotion, ctx);
+            game.game_events.clear();
+
+            if i + 1 < saved_game.moves.len() {
+                game.record_snapshot();
+            }
+        }
+
+        game.animation = None;
+        game.pending_sound = None;
+
+        if let (Some(clock), Some(remaining)) = (game.clock.as_mut(), saved_game.clock) {
+            clock.remaining = remaining;
+        }
+
+        if !saved_game.moves.is_empty() {
+            game.game_events.push(GameEvent::CheckForGameEnd);
+        }
+
+        Ok(game)
+    }
+
+    pub fn to_saved_game(&self) -> SavedGame {
+        SavedGame {
+            moves: self.uci_moves.clone(),
+            config: self.config.clone(),
+            clock: self.clock.as_ref().map(|clock| clock.remaining),
+            flipped: self.flipped,
+        }
+    }
+
+    // the game as the other side of a network game sees it
+    pub fn to_peer_saved_game(&self) -> SavedGame {
+        let mut saved_game = self.to_saved_game();
+        let peer_color = self.config.player_color.get_opponent();
+
+        saved_game.config.player_color = peer_color;
+        saved_game.config.opponent = Opponent::LanJoin(String::new());
+        saved_game.flipped = peer_color == Player::Black;
+
+        saved_game
+    }
+
+    pub fn record_snapshot(&mut self) -> u8 {
+        let count = self.snapshots.entry(self.board.get_snapshot()).or_insert(0);
+        *count += 1;
+
+        *count
+    }
+
+    pub fn end_game(&mut self, end_type: EndType, ctx: &mut Context) {
+        // puzzles report their own result
+        if !self.is_puzzle {
+            self.modals = Some(Box::new(EndgameModal::new(end_type.clone(), ctx)));
+        }
+
+        self.end_type = Some(end_type);
+    }
+
+    pub fn update_clock(&mut self, ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        let is_human = self.is_human_player(self.current_player);
+
+        let Some(clock) = self.clock.as_mut() else {
+            return;
+        };
+
+        if self.history.is_empty() {
+            return;
+        }
+
+        if clock.tick(self.current_player, ctx.time.delta().as_secs_f32()) && is_human {
+            app_events.push(AppEvent::PlaySound(SoundEffect::LowTime));
+        }
+
+        if clock.is_flagged(self.current_player) {
+            let winner = self.current_player.get_opponent();
+
+            if self.get_total_pieces().has_mating_material(winner) {
+                self.end_game(EndType::Timeout(winner), ctx);
+            }
+            else {
+                self.end_game(EndType::InsufficientMaterial, ctx);
+            }
+
+            SavedGame::delete_autosave();
+            app_events.push(AppEvent::PlaySound(SoundEffect::GameEnd));
+        }
+    }
+
+    fn get_controller_index(player: Player) -> usize {
+        if player == Player::White { 0 } else { 1 }
+    }
+
+    pub fn set_controller(&mut self, player: Player, controller: Box<dyn PlayerController>) {
+        self.controllers[Self::get_controller_index(player)] = controller;
+    }
+
+    // asks the controller of the player to move for a move and plays it
+    pub fn update_controller(&mut self, ctx: &mut Context) {
+        let request = MoveRequest {
+            board: &self.board,
+            player: self.current_player,
+            start_fen: &self.start_fen,
+            moves: &self.uci_moves,
+            fifty_move_rule: self.fifty_move_rule,
+            clock: self.clock.as_ref(),
+            ply: self.history.len(),
+        };
+
+        let controller = &mut self.controllers[Self::get_controller_index(self.current_player)];
+        let kind = controller.get_kind();
+        let is_human = controller.is_human();
+
+        let Some(result) = controller.poll_move(&request) else {
+            return;
+        };
+
+        let found_move = result.and_then(|controller_move| {
+            let uci = &controller_move.uci;
+            let (from_pos, to_pos, promotion) = notation::parse_uci(uci).ok_or(format!("invalid move '{}'", uci))?;
+            let piece_move = self.board.find_move(self.current_player, from_pos, to_pos).ok_or(format!("illegal move '{}'", uci))?;
+
+            Ok((piece_move, promotion, controller_move.clock))
+        });
+
+        match found_move {
+            Ok((piece_move, promotion, clock)) => {
+                // people pick their promotion after the move, everyone else gets a queen
+                let promotion = if is_human { promotion } else { promotion.or(Some(PieceType::Queen)) };
+
+                self.apply_move(piece_move, promotion, ctx);
+
+                if let (Some(clock), Some(remaining)) = (self.clock.as_mut(), clock) {
+                    clock.remaining = remaining;
+                }
+            },
+            Err(error) if kind == ControllerKind::Network => {
+                println!("Network error: {}", error);
+
+                if let Some(network) = self.network.as_mut() {
+                    network.send(NetMessage::Error { message: error });
+                }
+            },
+            Err(error) => {
+                println!("{:?} error: {}, handing {:?} over to the player", kind, error, self.current_player);
+
+                self.set_controller(self.current_player, Box::new(MouseController::default()));
+            },
+        }
+    }
+
+    // moves made on the board go through the controller like any other
+    pub fn submit_move(&mut self, piece_move: &dyn ChessMove, promotion: Option<PieceType>, ctx: &mut Context) {
+        let controller_move = ControllerMove {
+            ply: self.history.len(),
+            uci: notation::get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion),
+            clock: None,
+        };
+
+        self.controllers[Self::get_controller_index(self.current_player)].submit_move(controller_move);
+        self.update_controller(ctx);
+    }
+
+    pub fn update_network(&mut self, ctx: &mut Context) {
+        let Some(network) = self.network.as_mut() else {
+            return;
+        };
+
+        for message in network.poll() {
+            match message {
+                NetMessage::Hello { .. } => {
+                    let saved_game = self.to_peer_saved_game();
+
+                    if let Some(network) = self.network.as_mut() {
+                        network.welcome(saved_game);
+                    }
+                },
+                NetMessage::Welcome { game, .. } => self.resync(&game),
+                NetMessage::Move { ply, uci, clock } => self.receive_move(ply, uci, clock),
+                NetMessage::ClockSync { remaining } => {
+                    if let Some(clock) = self.clock.as_mut() {
+                        clock.remaining = remaining;
+                    }
+                },
+                NetMessage::AnswerDraw { accepted: true } if self.end_type.is_none() => self.end_game(EndType::Agreement, ctx),
+                NetMessage::Resign if self.end_type.is_none() => self.end_game(EndType::Resignation(self.config.player_color), ctx),
+                _ => (),
+            }
+        }
+
+        if let (Some(network), Some(clock), None) = (self.network.as_mut(), self.clock.as_ref(), &self.end_type) {
+            network.sync_clock(clock.remaining);
+        }
+    }
+
+    // hands a move of the opponent to its controller, which plays it in order
+    pub fn receive_move(&mut self, ply: usize, uci: String, clock: Option<[f32; 2]>) {
+        // already played, the move was sent again after a reconnection
+        if ply < self.history.len() {
+            return;
+        }
+
+        let mover = if ply % 2 == 1 { self.start_player.get_opponent() } else { self.start_player };
+        let controller = &mut self.controllers[Self::get_controller_index(mover)];
+
+        if controller.get_kind() != ControllerKind::Network || self.end_type.is_some() {
+            let error = format!("'{}' was played out of turn", uci);
+            println!("Network error: {}", error);
+
+            if let Some(network) = self.network.as_mut() {
+                network.send(NetMessage::Error { message: error });
+            }
+
+            return;
+        }
+
+        controller.submit_move(ControllerMove { ply, uci, clock });
+    }
+
+    // catches up with the host after a reconnection
+    pub fn resync(&mut self, saved_game: &SavedGame) {
+        for (ply, uci) in saved_game.moves.iter().enumerate().skip(self.history.len()) {
+            self.receive_move(ply, uci.clone(), None);
+        }
+
+        let unsent_moves: Vec<(usize, String)> = self.history.iter()
+            .enumerate()
+            .skip(saved_game.moves.len())
+            .map(|(ply, entry)| (ply, notation::get_uci(entry.from_pos, entry.to_pos, entry.promotion)))
+            .collect();
+
+        if let Some(network) = self.network.as_mut() {
+            for (ply, uci) in unsent_moves {
+                network.send(NetMessage::Move { ply, uci, clock: None });
+            }
+        }
+
+        if let (Some(clock), Some(remaining)) = (self.clock.as_mut(), saved_game.clock) {
+            clock.remaining = remaining;
+        }
+    }
+
+    pub fn get_position(&self, ply: usize) -> (&Board, Player) {
+        match ply {
+            0 => (&self.start_board, self.start_player),
+            ply => (&self.history[ply - 1].board, self.history[ply - 1].current_player),
+        }
+    }
+
+    pub fn get_analysed_position(&self) -> (&Board, Player) {
+        match self.viewed_ply {
+            None => (&self.board, self.current_player),
+            Some(ply) => self.get_position(ply),
+        }
+    }
+
+    pub fn update_analysis(&mut self) {
+        if self.analyser.is_none() || self.pending_promotion.is_some() {
+            return;
+        }
+
+        let (board, player) = self.get_analysed_position();
+        let board = board.clone();
+        let fen = fen::get_fen(&board, player, 0, 1);
+
+        if self.analysed_fen.as_ref() != Some(&fen) {
+            let fifty_move_rule = if self.viewed_ply.is_none() { self.fifty_move_rule } else { 0 };
+
+            self.book_moves = self.get_book_moves(&board, player);
+            self.tablebase_probe = self.engine_options.tablebase.as_ref().and_then(|tablebase| tablebase.probe(&board, player, fifty_move_rule));
+        }
+
+        let analyser = self.analyser.as_mut().unwrap();
+
+        if self.analysed_fen.as_ref() != Some(&fen) {
+            analyser.start(&board, player, fen.clone());
+
+            self.analysed_fen = Some(fen);
+            self.analysis_san.clear();
+            self.preview = None;
+        }
+
+        if analyser.poll() {
+            self.analysis_san = analyser.analysis.lines.iter()
+                .map(|line| notation::get_san_line(&board, player, &line.pv))
+                .collect();
+        }
+    }
+
+    fn get_book_moves(&self, board: &Board, player: Player) -> Vec<(String, u16)> {
+        let Some(book) = &self.engine_options.book else {
+            return Vec::new();
+        };
+
+        book.get_moves(board, player).into_iter()
+            .filter_map(|book_move| Some((notation::get_san_line(board, player, &[book_move.uci]).pop()?, book_move.weight)))
+            .collect()
+    }
+
+    // the book moves or the tablebase result take the place of the last line
+    fn get_shown_analysis_lines(&self) -> usize {
+        if self.book_moves.is_empty() && self.tablebase_probe.is_none() { ANALYSIS_LINES } else { ANALYSIS_LINES - 1 }
+    }
+
+    pub fn get_analysis_line_at(&self, screen_position: Vec2) -> Option<usize> {
+        if self.analyser.is_none() || screen_position.x < 290. || screen_position.x > 446. {
+            return None;
+        }
+
+        (0..self.analysis_san.len().min(self.get_shown_analysis_lines())).find(|line| {
+            let y = 146. + 30. * *line as f32;
+
+            screen_position.y > y && screen_position.y < y + 28.
+        })
+    }
+
+    pub fn toggle_preview(&mut self, line: usize) {
+        if self.preview.as_ref().is_some_and(|(previewed_line, _)| *previewed_line == line) {
+            self.preview = None;
+            return;
+        }
+
+        let Some(analyser) = &self.analyser else {
+            return;
+        };
+
+        let (board, mut player) = self.get_analysed_position();
+        let mut board = board.clone();
+
+        for uci in analyser.analysis.lines[line].pv.iter() {
+            if notation::play_uci(&mut board, player, uci).is_none() {
+                break;
+            }
+
+            player = player.get_opponent();
+        }
+
+        self.preview = Some((line, board));
+    }
+
+    pub fn update_move_list_layout(&mut self) {
+        let has_panel = self.analyser.is_some() || self.review_panel.is_some() || self.network_panel.is_some() || self.is_puzzle;
+
+        match (has_panel, self.eval_graph.is_visible) {
+            (false, false) => self.move_list.set_layout(30., 13),
+            (true, false) => self.move_list.set_layout(30., 6),
+            (false, true) => self.move_list.set_layout(130., 6),
+            (true, true) => self.move_list.set_layout(30., 0),
+        }
+    }
+
+    // starts evaluating every position of the game in the background, once
+    pub fn start_evaluations(&mut self) {
+        if self.evaluations.is_some() {
+            return;
+        }
+
+        let mut positions = vec![(self.start_board.clone(), self.start_player)];
+
+        positions.extend(self.history.iter().map(|entry| (entry.board.clone(), entry.current_player)));
+
+        let played_moves = self.history.iter()
+            .map(|entry| notation::get_uci(entry.from_pos, entry.to_pos, entry.promotion))
+            .collect();
+
+        self.evaluations = Some(GameReview::start(positions, played_moves));
+    }
+
+    pub fn start_review(&mut self, ctx: &mut Context) {
+        self.start_evaluations();
+
+        self.analyser = None;
+        self.analysis_san.clear();
+        self.preview = None;
+        self.review_panel = Some(ReviewPanel::new(ctx));
+        self.eval_graph.is_visible = true;
+
+        self.update_move_list_layout();
+    }
+
+    pub fn apply_move(&mut self, piece_move: Box<dyn ChessMove>, promotion: Option<PieceType>, ctx: &mut Context) {
+        self.save_result = None;
+
+        if let Some(clock) = self.clock.as_mut() {
+            clock.add_increment(self.current_player);
+        }
+
+        self.fifty_move_rule += 1;
+        if !piece_move.is_castle_move() && self.board[piece_move.get_to_pos()].is_some()
+        || self.board[piece_move.get_from_pos()].clone().unwrap().get_piece_type() == PieceType::Pawn {
+            self.fifty_move_rule = 0;
+        }
+        
+        let board_before = self.board.clone();
+
+        self.pending_sound = Some(if piece_move.is_castle_move() {
+            SoundEffect::Castle
+        } else if board_before[piece_move.get_to_pos()].is_some()
+        || board_before[piece_move.get_from_pos()].as_ref().unwrap().get_piece_type() == PieceType::Pawn
+        && piece_move.get_from_pos().column != piece_move.get_to_pos().column {
+            SoundEffect::Capture
+        } else {
+            SoundEffect::Move
+        });
+
+        piece_move.execute(&mut self.board);
+
+        self.start_animation(Animation::from_move(&board_before, piece_move.as_ref(), self.animation_duration));
+
+        self.last_move = Some((piece_move.get_from_pos(), piece_move.get_to_pos()));
+
+        self.current_player = self.current_player.get_opponent();
+
+        if piece_move.is_pawn_promotion_move() {
+            if let Some(piece_type) = promotion.or(if self.auto_queen { Some(PieceType::Queen) } else { None }) {
+                self.board.promote(piece_move.get_to_pos(), piece_type);
+                self.record_move(&board_before, piece_move.as_ref(), Some(piece_type));
+                self.pending_sound = Some(SoundEffect::Promotion);
+                self.game_events.push(GameEvent::CheckForGameEnd);
+            }
+            else {
+                self.modals = Some(Box::new(PawnPromotionModal::new(ctx, piece_move.get_to_pos())) as Box<dyn Modal>);
+                self.pending_promotion = Some((board_before, piece_move));
+            }
+        }
+        else {
+            self.record_move(&board_before, piece_move.as_ref(), None);
+            self.game_events.push(GameEvent::CheckForGameEnd);
+        }
+    }
+
+    pub fn is_human_player(&self, player: Player) -> bool {
+        self.controllers[Self::get_controller_index(player)].is_human()
+    }
+
+    pub fn get_premove_board(&self) -> Board {
+        let mut premove_board = self.board.clone();
+
+        for premove in self.premoves.iter() {
+            let from_positions = [Some(premove.get_from_pos()), premove.get_castle_rook().map(|(rook_from, _)| rook_from)];
+
+            // the opponent may have taken a piece of the premove since
+            if from_positions.into_iter().flatten().all(|position| premove_board[position].is_some()) {
+                premove.execute(&mut premove_board);
+            }
+        }
+
+        premove_board
+    }
+
+    pub fn get_premove_moves(&self, position: Position) -> Vec<Box<dyn ChessMove>> {
+        let mut premove_board = self.get_premove_board();
+        let piece = premove_board[position].clone().unwrap();
+
+        for col in premove_board.state.iter_mut() {
+            for square in col.iter_mut() {
+                if square.as_ref().is_some_and(|other| other.get_color() == piece.get_color()) {
+                    *square = None;
+                }
+            }
+        }
+
+        premove_board[position] = Some(piece.clone());
+
+        let mut premove_moves = piece.get_moves(position, &premove_board);
+
+        // castling needs the king's own rooks, which the other premoves ignore
+        if piece.get_piece_type() == PieceType::King {
+            let mut castle_board = self.get_premove_board();
+
+            for col in castle_board.state.iter_mut() {
+                for square in col.iter_mut() {
+                    if square.as_ref().is_some_and(|other| other.get_color() == piece.get_color() && !matches!(other.get_piece_type(), PieceType::King | PieceType::Rook)) {
+                        *square = None;
+                    }
+                }
+            }
+
+            let castle_moves: Vec<Box<dyn ChessMove>> = piece.get_moves(position, &castle_board).into_iter().filter(|piece_move| piece_move.is_castle_move()).collect();
+
+            premove_moves.retain(|piece_move| !castle_moves.iter().any(|castle_move| castle_move.get_to_pos() == piece_move.get_to_pos()));
+            premove_moves.extend(castle_moves);
+        }
+
+        if piece.get_piece_type() == PieceType::Pawn {
+            let direction = if piece.get_color() == Player::White { Direction::NORTH } else { Direction::SOUTH };
+
+            for side in [Direction::WEST, Direction::EAST] {
+                let to_pos = position + (direction + side).into();
+
+                if Board::is_inside(to_pos) && !premove_moves.iter().any(|piece_move| piece_move.get_to_pos() == to_pos) {
+                    premove_moves.push(Box::new(NormalMove::new(position, to_pos)));
+                }
+            }
+        }
+
+        premove_moves
+    }
+
+    pub fn play_premove(&mut self, ctx: &mut Context) {
+        let premove = self.premoves.remove(0);
+        let (from_pos, to_pos) = (premove.get_from_pos(), premove.get_to_pos());
+
+        let piece_move = self.board.get_legal_moves_from(from_pos)
+            .into_iter()
+            .find(|piece_move| piece_move.get_to_pos() == to_pos && self.board[from_pos].as_ref().unwrap().get_color() == self.current_player);
+
+        match piece_move {
+            Some(piece_move) => {
+                let promotion = piece_move.is_pawn_promotion_move().then_some(PieceType::Queen);
+
+                self.submit_move(piece_move.as_ref(), promotion, ctx);
+            },
+            None => self.premoves.clear(),
+        }
+    }
+
+    pub fn start_animation(&mut self, animation: Animation) {
+        if self.animation_duration > 0. {
+            self.animation = Some(animation);
+        }
+    }
+
+    pub fn record_move(&mut self, board_before: &Board, piece_move: &dyn ChessMove, promotion: Option<PieceType>) {
+        let san = notation::get_san(board_before, piece_move, promotion);
+
+        self.uci_moves.push(notation::get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion));
+        self.history.push(HistoryEntry {
+            board: self.board.clone(),
+            san,
+            from_pos: piece_move.get_from_pos(),
+            to_pos: piece_move.get_to_pos(),
+            promotion,
+            current_player: self.current_player,
+            is_checked: self.board.get_check(self.current_player),
+        });
+
+        let mover = self.current_player.get_opponent();
+
+        let is_human_move = self.is_human_player(mover);
+
+        if let (Some(network), false) = (self.network.as_mut(), is_human_move) {
+            // an unanswered offer lapses with the opponent's move
+            if network.draw_offer == DrawOffer::Sent {
+                network.draw_offer = DrawOffer::None;
+            }
+        }
+
+        if let (Some(network), true) = (self.network.as_mut(), is_human_move) {
+            if network.draw_offer == DrawOffer::Received {
+                network.draw_offer = DrawOffer::None;
+                network.send(NetMessage::AnswerDraw { accepted: false });
+            }
+
+            network.send(NetMessage::Move {
+                ply: self.history.len() - 1,
+                uci: notation::get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion),
+                clock: self.clock.as_ref().map(|clock| clock.remaining),
+            });
+        }
+
+        if let Some(evaluations) = self.evaluations.as_mut() {
+            evaluations.add_position(self.board.clone(), self.current_player, notation::get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion));
+        }
+    }
+
+    pub fn get_displayed_board(&self) -> &Board {
+        if let Some((_, board)) = &self.preview {
+            return board;
+        }
+
+        match self.viewed_ply {
+            None => &self.board,
+            Some(0) => &self.start_board,
+            Some(ply) => &self.history[ply - 1].board,
+        }
+    }
+
+    pub fn get_displayed_last_move(&self) -> Option<(Position, Position)> {
+        match self.viewed_ply {
+            None => self.last_move,
+            Some(0) => None,
+            Some(ply) => Some((self.history[ply - 1].from_pos, self.history[ply - 1].to_pos)),
+        }
+    }
+
+    pub fn get_displayed_check(&self) -> Option<Player> {
+        match self.viewed_ply {
+            None => self.is_checked.then_some(self.current_player),
+            Some(0) => None,
+            Some(ply) => {
+                let entry = &self.history[ply - 1];
+
+                entry.is_checked.then_some(entry.current_player)
+            },
+        }
+    }
+
+    pub fn get_displayed_ply(&self) -> usize {
+        self.viewed_ply.unwrap_or(self.history.len())
+    }
+
+    pub fn show_ply(&mut self, ply: usize) {
+        if ply >= self.history.len() {
+            self.viewed_ply = None;
+        }
+        else {
+            self.viewed_ply = Some(ply);
         }
     }
 
@@ -58,86 +863,478 @@ impl Game {
                             
                             piece_move.execute(&mut test_board);
 
-                            !test_board.get_check(self.current_player)
-                        });
+                            !test_board.get_check(self.current_player)
+                        });
+
+                        if !piece_moves.is_empty() {
+                            found_moves = true;
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+
+        !found_moves        
+    }
+
+    pub fn is_king_v_king(&self, pieces: PieceCounting) -> bool {
+        pieces.total_pieces == 2
+    }
+
+    pub fn is_king_v_king_knight(&self, pieces: PieceCounting) -> bool {
+        pieces.total_pieces == 3 
+        && (pieces.white_pieces.get(&PieceType::Knight).is_some() 
+        || pieces.black_pieces.get(&PieceType::Knight).is_some())
+    }
+
+    pub fn is_king_v_king_bishop(&self, pieces: PieceCounting) -> bool {
+        pieces.total_pieces == 3 
+        && (pieces.white_pieces.get(&PieceType::Bishop).is_some() 
+        || pieces.black_pieces.get(&PieceType::Bishop).is_some())
+    }
+    
+    pub fn is_king_bishop_v_king_bishop(&self, pieces: PieceCounting) -> bool {
+        if pieces.total_pieces == 4
+        && pieces.white_pieces.get(&PieceType::Bishop).is_some() 
+        && pieces.black_pieces.get(&PieceType::Bishop).is_some() {
+            let white_bishop_pos = self.board.find_piece(Player::White, PieceType::Bishop);
+            let black_bishop_pos = self.board.find_piece(Player::Black, PieceType::Bishop);
+
+            if (white_bishop_pos.column + white_bishop_pos.row + black_bishop_pos.column + black_bishop_pos.row) % 2 != 0 {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces = self.get_total_pieces();
+
+        self.is_king_v_king(pieces.clone()) || self.is_king_v_king_knight(pieces.clone())
+        || self.is_king_v_king_bishop(pieces.clone()) || self.is_king_bishop_v_king_bishop(pieces.clone())
+    }
+
+    pub fn get_total_pieces(&self) -> PieceCounting {
+        self.board.get_total_pieces()
+    }
+
+    pub fn get_piece_asset_id(player: Player, piece_type: PieceType) -> String {
+        let piece_asset_type = match piece_type {
+            PieceType::Pawn => "pawn",
+            PieceType::Knight => "knight",
+            PieceType::Bishop => "bishop",
+            PieceType::Rook => "rook",
+            PieceType::Queen => "queen",
+            PieceType::King => "king",
+        };
+
+        let piece_asset_player = match player {
+            Player::Black => "black",
+            Player::White => "white",
+        };
+
+        String::from(piece_asset_player) + piece_asset_type
+    }
+
+    pub fn get_square_highlight_id(position: Position, highlight_type: &str) -> String {
+        let square_color = match position.square_color() {
+            Player::White => "white",
+            Player::Black => "black",
+        };
+
+        String::from(square_color) + highlight_type
+    }
+
+    pub fn get_square_coordinates(&self, position: Position) -> [f32; 2] {
+        let (column, row) = if self.flipped {
+            (7 - position.column, 7 - position.row)
+        } else {
+            (position.column, position.row)
+        };
+
+        [(32*column+16) as f32, (32*row+16) as f32]
+    }
+
+    pub fn get_square_at(&self, screen_position: Vec2) -> Option<Position> {
+        if screen_position.x > 16. && screen_position.x < 272.
+        && screen_position.y > 16. && screen_position.y < 272. {
+            let column = ((screen_position.x - 16.) / 32.) as i32;
+            let row = ((screen_position.y - 16.) / 32.) as i32;
+
+            if self.flipped {
+                return Some(Position::new(7 - column, 7 - row));
+            }
+
+            return Some(Position::new(column, row));
+        }
+
+        None
+    }
+
+    pub fn draw_coordinates(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let border_color = assets.border_color;
+        let label_color = assets.label_color;
+
+        canvas.draw(&graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(2., 16., 12., 256.),
+            border_color
+        ).unwrap(), [0., 0.]);
+
+        canvas.draw(&graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(16., 274., 256., 12.),
+            border_color
+        ).unwrap(), [0., 0.]);
+
+        for i in 0..8 {
+            let file = if self.flipped { b'h' - i as u8 } else { b'a' + i as u8 } as char;
+            let rank = if self.flipped { i + 1 } else { 8 - i };
+
+            let mut file_text = Text::new(file.to_string());
+            file_text.set_scale(10.);
+            let file_dimensions = file_text.dimensions(ctx).unwrap();
+
+            canvas.draw(
+                &file_text,
+                DrawParam::new()
+                    .dest([(32*i+32) as f32 - file_dimensions.w/2., 280. - file_dimensions.h/2.])
+                    .color(label_color)
+            );
+
+            let mut rank_text = Text::new(rank.to_string());
+            rank_text.set_scale(10.);
+            let rank_dimensions = rank_text.dimensions(ctx).unwrap();
+
+            canvas.draw(
+                &rank_text,
+                DrawParam::new()
+                    .dest([8. - rank_dimensions.w/2., (32*i+32) as f32 - rank_dimensions.h/2.])
+                    .color(label_color)
+            );
+        }
+    }
+
+    pub fn draw_arrow(&self, from_pos: Position, to_pos: Position, color: Color, canvas: &mut Canvas, ctx: &mut Context) {
+        let from = Vec2::from(self.get_square_coordinates(from_pos)) + Vec2::new(16., 16.);
+        let to = Vec2::from(self.get_square_coordinates(to_pos)) + Vec2::new(16., 16.);
+
+        let direction = (to - from).normalize();
+        let normal = direction.perp();
+
+        let start = from + direction * 8.;
+        let head = to - direction * 12.;
+
+        let points = [
+            start + normal * 3.,
+            head + normal * 3.,
+            head + normal * 8.,
+            to,
+            head - normal * 8.,
+            head - normal * 3.,
+            start - normal * 3.,
+        ];
+
+        canvas.draw(&graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &points,
+            color
+        ).unwrap(), [0., 0.]);
+    }
+
+    pub fn draw_annotations(&self, canvas: &mut Canvas, ctx: &mut Context) {
+        if let Some(annotations) = self.annotations.get(&self.get_displayed_ply()) {
+            for (pos, annotation_color) in annotations.marks.iter() {
+                let center = Vec2::from(self.get_square_coordinates(*pos)) + Vec2::new(16., 16.);
 
-                        if !piece_moves.is_empty() {
-                            found_moves = true;
-                            break 'outer;
-                        }
+                canvas.draw(&graphics::Mesh::new_circle(
+                    ctx,
+                    graphics::DrawMode::stroke(2.),
+                    center,
+                    14.,
+                    0.5,
+                    annotation_color.get_color()
+                ).unwrap(), [0., 0.]);
+            }
+
+            for (from_pos, to_pos, annotation_color) in annotations.arrows.iter() {
+                self.draw_arrow(*from_pos, *to_pos, annotation_color.get_color(), canvas, ctx);
+            }
+        }
+
+        if let Some(from_pos) = self.annotation_start {
+            if ctx.mouse.button_pressed(MouseButton::Right) {
+                if let Some(to_pos) = self.get_square_at(ctx.mouse.position().into()) {
+                    if to_pos != from_pos {
+                        let annotation_color = AnnotationColor::from_mods(ctx.keyboard.active_mods());
+
+                        self.draw_arrow(from_pos, to_pos, annotation_color.get_color(), canvas, ctx);
                     }
                 }
             }
         }
-
-        !found_moves        
     }
 
-    pub fn is_king_v_king(&self, pieces: PieceCounting) -> bool {
-        pieces.total_pieces == 2
-    }
+    pub fn draw_animation(&self, animation: &Animation, assets: &Assets, canvas: &mut Canvas) {
+        let progress = animation.get_progress();
 
-    pub fn is_king_v_king_knight(&self, pieces: PieceCounting) -> bool {
-        pieces.total_pieces == 3 
-        && (pieces.white_pieces.get(&PieceType::Knight).is_some() 
-        || pieces.black_pieces.get(&PieceType::Knight).is_some())
-    }
+        for animated_piece in animation.pieces.iter() {
+            let from = self.get_square_coordinates(animated_piece.position);
+            let piece_asset_id = Game::get_piece_asset_id(animated_piece.player, animated_piece.piece_type);
 
-    pub fn is_king_v_king_bishop(&self, pieces: PieceCounting) -> bool {
-        pieces.total_pieces == 3 
-        && (pieces.white_pieces.get(&PieceType::Bishop).is_some() 
-        || pieces.black_pieces.get(&PieceType::Bishop).is_some())
+            match animated_piece.kind {
+                AnimationKind::Slide(to_pos) => {
+                    let to = self.get_square_coordinates(to_pos);
+
+                    canvas.draw(
+                        assets.graphics.get(&piece_asset_id).unwrap(),
+                        [from[0] + (to[0] - from[0]) * progress, from[1] + (to[1] - from[1]) * progress]
+                    );
+                },
+                AnimationKind::FadeOut => {
+                    canvas.draw(
+                        assets.graphics.get(&piece_asset_id).unwrap(),
+                        DrawParam::new()
+                            .dest(from)
+                            .color(Color::new(1., 1., 1., 1. - progress))
+                    );
+                },
+                AnimationKind::Morph(piece_type) => {
+                    canvas.draw(
+                        assets.graphics.get(&piece_asset_id).unwrap(),
+                        DrawParam::new()
+                            .dest(from)
+                            .color(Color::new(1., 1., 1., 1. - progress))
+                    );
+                    canvas.draw(
+                        assets.graphics.get(&Game::get_piece_asset_id(animated_piece.player, piece_type)).unwrap(),
+                        DrawParam::new()
+                            .dest(from)
+                            .color(Color::new(1., 1., 1., progress))
+                    );
+                },
+            }
+        }
     }
-    
-    pub fn is_king_bishop_v_king_bishop(&self, pieces: PieceCounting) -> bool {
-        if pieces.total_pieces == 4
-        && pieces.white_pieces.get(&PieceType::Bishop).is_some() 
-        && pieces.black_pieces.get(&PieceType::Bishop).is_some() {
-            let white_bishop_pos = self.board.find_piece(Player::White, PieceType::Bishop);
-            let black_bishop_pos = self.board.find_piece(Player::Black, PieceType::Bishop);
 
-            if (white_bishop_pos.column + white_bishop_pos.row + black_bishop_pos.column + black_bishop_pos.row) % 2 != 0 {
-                return true;
+    pub fn draw_material(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let pieces = self.get_displayed_board().get_total_pieces();
+        let start_pieces = self.start_board.get_total_pieces();
+        let advantage = pieces.get_material(Player::White) - pieces.get_material(Player::Black);
+
+        let (top_player, bottom_player) = if self.flipped {
+            (Player::White, Player::Black)
+        } else {
+            (Player::Black, Player::White)
+        };
+
+        for (player, y) in [(top_player, 244.), (bottom_player, 266.)] {
+            let mut x = 292.;
+
+            for piece_type in pieces.get_captured_pieces(&start_pieces, player) {
+                canvas.draw(
+                    assets.graphics.get(&Game::get_piece_asset_id(player.get_opponent(), piece_type)).unwrap(),
+                    DrawParam::new()
+                        .dest([x, y])
+                        .scale([0.5, 0.5])
+                );
+
+                x += 9.;
+            }
+
+            let player_advantage = if player == Player::White { advantage } else { -advantage };
+
+            if player_advantage > 0 {
+                let mut advantage_text = Text::new(format!("+{}", player_advantage));
+                advantage_text.set_scale(14.);
+
+                canvas.draw(
+                    &advantage_text,
+                    DrawParam::new()
+                        .dest([x + 10., y + 8. - advantage_text.dimensions(ctx).unwrap().h/2.])
+                        .color(Color::BLACK)
+                );
             }
         }
+    }
 
-        false
+    pub fn draw_clocks(&self, canvas: &mut Canvas, ctx: &mut Context) {
+        let Some(clock) = &self.clock else {
+            return;
+        };
+
+        let (top_player, bottom_player) = if self.flipped {
+            (Player::White, Player::Black)
+        } else {
+            (Player::Black, Player::White)
+        };
+
+        for (player, y) in [(top_player, 244.), (bottom_player, 266.)] {
+            let mut clock_text = Text::new(clock.format(player));
+            clock_text.set_scale(16.);
+
+            let dimensions = clock_text.dimensions(ctx).unwrap();
+            let is_active = player == self.current_player && self.end_type.is_none() && !self.history.is_empty();
+
+            let color = if clock.get_remaining(player) < 10. {
+                Color::RED
+            } else if is_active {
+                Color::BLACK
+            } else {
+                Color::new(0.4, 0.4, 0.4, 1.)
+            };
+
+            canvas.draw(
+                &clock_text,
+                DrawParam::new()
+                    .dest([444. - dimensions.w, y + 8. - dimensions.h/2.])
+                    .color(color)
+            );
+        }
     }
 
-    pub fn is_insufficient_material(&self) -> bool {
-        let pieces = self.get_total_pieces();
+    pub fn draw_prompt(&self, canvas: &mut Canvas) {
+        let prompt = self.controllers[Self::get_controller_index(self.current_player)].get_prompt();
 
-        self.is_king_v_king(pieces.clone()) || self.is_king_v_king_knight(pieces.clone())
-        || self.is_king_v_king_bishop(pieces.clone()) || self.is_king_bishop_v_king_bishop(pieces.clone())
+        if let (Some(prompt), None) = (prompt, &self.end_type) {
+            let mut prompt_text = Text::new(prompt);
+            prompt_text.set_scale(12.);
+            prompt_text.set_bounds([256., 14.]);
+
+            canvas.draw(&prompt_text, DrawParam::new().dest([16., 2.]).color(Color::BLACK));
+
+            return;
+        }
+
+        if let Some(save_result) = &self.save_result {
+            let (message, color) = match save_result {
+                Ok(file_name) => (format!("Saved game to {}", file_name), Color::BLACK),
+                Err(error) => (format!("Could not save game: {}", error), Color::RED),
+            };
+
+            let mut save_text = Text::new(message);
+            save_text.set_scale(12.);
+            save_text.set_bounds([256., 14.]);
+
+            canvas.draw(&save_text, DrawParam::new().dest([16., 2.]).color(color));
+        }
     }
 
-    pub fn get_total_pieces(&self) -> PieceCounting {
-        let mut total_pieces = 0;
-        let mut black_pieces = HashMap::new();
-        let mut white_pieces = HashMap::new();
+    pub fn draw_analysis(&self, canvas: &mut Canvas, ctx: &mut Context) {
+        let Some(analyser) = &self.analyser else {
+            return;
+        };
 
-        for col in self.board.state.iter() {
-            for piece in col.into_iter() {
-                if let Some(piece) = piece {
-                    if piece.get_color() == Player::Black {
-                        black_pieces.insert(piece.get_piece_type(), black_pieces.get(&piece.get_piece_type()).unwrap_or(&0) + 1);
-                    }
-                    else {
-                        white_pieces.insert(piece.get_piece_type(), white_pieces.get(&piece.get_piece_type()).unwrap_or(&0) + 1);
-                    }
+        let analysis = &analyser.analysis;
+        let fraction = analysis.lines.first().map(|line| line.score.get_bar_fraction()).unwrap_or(0.5);
+        let white_height = 256. * fraction;
 
-                    total_pieces += 1;
-                }
+        canvas.draw(&graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., 0., 8., 256.),
+            Color::from_rgb(64, 64, 64)
+        ).unwrap(), [276., 16.]);
+
+        canvas.draw(&graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., 0., 8., white_height),
+            Color::from_rgb(240, 240, 240)
+        ).unwrap(), [276., if self.flipped { 16. } else { 272. - white_height }]);
+
+        if self.preview.is_none() {
+            if let Some((from_pos, to_pos, _)) = analysis.lines.first().and_then(|line| line.pv.first()).and_then(|uci| notation::parse_uci(uci)) {
+                self.draw_arrow(from_pos, to_pos, Color::new(0.1, 0.3, 0.9, 0.6), canvas, ctx);
+            }
+        }
+
+        let header = match &analyser.error {
+            Some(error) => error.clone(),
+            None => format!("{}  depth {}", analyser.get_name(), analysis.depth),
+        };
+
+        let mut header_text = Text::new(header);
+        header_text.set_scale(12.);
+        header_text.set_bounds([152., 14.]);
+
+        canvas.draw(&header_text, DrawParam::new().dest([292., 130.]).color(Color::BLACK));
+
+        for (i, (line, san_moves)) in analysis.lines.iter().zip(self.analysis_san.iter()).take(self.get_shown_analysis_lines()).enumerate() {
+            let y = 146. + 30. * i as f32;
+
+            if self.preview.as_ref().is_some_and(|(previewed_line, _)| *previewed_line == i) {
+                canvas.draw(&graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 156., 28.),
+                    Color::from_rgb(199, 207, 204)
+                ).unwrap(), [290., y]);
             }
+
+            let mut line_text = Text::new(format!("{} {}", line.score.format(), san_moves.join(" ")));
+            line_text.set_scale(12.);
+            line_text.set_bounds([152., 28.]);
+
+            canvas.draw(&line_text, DrawParam::new().dest([292., y]).color(Color::BLACK));
         }
 
-        PieceCounting {
-            total_pieces,
-            black_pieces,
-            white_pieces
+        let extra_line = if let Some(probe) = &self.tablebase_probe {
+            Some(probe.format())
+        } else if !self.book_moves.is_empty() {
+            let total: u32 = self.book_moves.iter().map(|(_, weight)| *weight as u32).sum::<u32>().max(1);
+
+            let book_moves: Vec<String> = self.book_moves.iter()
+                .map(|(san, weight)| format!("{} {}%", san, *weight as u32 * 100 / total))
+                .collect();
+
+            Some(format!("Book: {}", book_moves.join(", ")))
+        } else {
+            None
+        };
+
+        if let Some(extra_line) = extra_line {
+            let mut extra_text = Text::new(extra_line);
+            extra_text.set_scale(12.);
+            extra_text.set_bounds([152., 28.]);
+
+            canvas.draw(&extra_text, DrawParam::new().dest([292., 146. + 30. * (ANALYSIS_LINES - 1) as f32]).color(Color::BLACK));
         }
     }
 
+    pub fn draw_hover(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let mouse_position: Vec2 = ctx.mouse.position().into();
+
+        if let Some(pos) = self.get_square_at(mouse_position) {
+            canvas.draw(&graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(2.),
+                Rect::new(1., 1., 30., 30.),
+                Color::new(1., 1., 1., 0.8)
+            ).unwrap(), self.get_square_coordinates(pos));
+
+            let mut readout = pos.to_algebraic();
+
+            if let Some(piece) = &self.get_displayed_board()[pos] {
+                readout += &format!(" {:?} {:?}", piece.get_color(), piece.get_piece_type());
+            }
+
+            let mut readout_text = Text::new(readout);
+            readout_text.set_scale(12.);
+
+            canvas.draw(
+                &readout_text,
+                DrawParam::new()
+                    .dest([16., 8. - readout_text.dimensions(ctx).unwrap().h/2.])
+                    .color(assets.label_color)
+            );
+        }
+    }
 
 }
 
@@ -145,6 +1342,59 @@ impl Scene for Game {
     fn draw(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
         canvas.draw(assets.graphics.get("board").unwrap(), [0., 0.]);
 
+        self.draw_coordinates(assets, canvas, ctx);
+
+        let board = self.get_displayed_board();
+
+        if self.show_threats && self.viewed_ply.is_none() {
+            for pos in board.get_attacked_squares(self.current_player.get_opponent()) {
+                canvas.draw(
+                    assets.graphics.get(&Game::get_square_highlight_id(pos, "highlight")).unwrap(),
+                    DrawParam::new()
+                        .dest(self.get_square_coordinates(pos))
+                        .color(Color::new(1., 1., 1., 0.4))
+                );
+            }
+        }
+
+        if let Some((from_pos, to_pos)) = self.get_displayed_last_move() {
+            for pos in [from_pos, to_pos] {
+                canvas.draw(
+                    assets.graphics.get(&Game::get_square_highlight_id(pos, "highlight")).unwrap(),
+                    self.get_square_coordinates(pos)
+                );
+            }
+        }
+
+        if let Some(pos) = self.selected_position {
+            canvas.draw(
+                assets.graphics.get(&Game::get_square_highlight_id(pos, "select")).unwrap(),
+                self.get_square_coordinates(pos)
+            );
+        }
+
+        if let Some(piece_move) = &self.pending_confirmation {
+            for pos in [piece_move.get_from_pos(), piece_move.get_to_pos()] {
+                canvas.draw(&graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::stroke(3.),
+                    Rect::new(1.5, 1.5, 29., 29.),
+                    Color::from_rgb(230, 143, 0)
+                ).unwrap(), self.get_square_coordinates(pos));
+            }
+        }
+
+        for premove in self.premoves.iter() {
+            for pos in [premove.get_from_pos(), premove.get_to_pos()] {
+                canvas.draw(&graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 32., 32.),
+                    Color::from_rgba(20, 85, 200, 110)
+                ).unwrap(), self.get_square_coordinates(pos));
+            }
+        }
+
         if let Some(legal_moves) = &self.legal_moves {
             for piece_move in legal_moves {
 
@@ -152,12 +1402,12 @@ impl Scene for Game {
                 if let Some(_) = self.board[piece_move.get_to_pos()] {
                     canvas.draw(
                         assets.graphics.get("capturehighlight").unwrap(),
-                        [(32*piece_move.get_to_pos().column+16) as f32, (32*piece_move.get_to_pos().row+16) as f32]
+                        self.get_square_coordinates(piece_move.get_to_pos())
                     )
                 } else {
                     canvas.draw(
                         assets.graphics.get("movehighlight").unwrap(),
-                        [(32*piece_move.get_to_pos().column+16) as f32, (32*piece_move.get_to_pos().row+16) as f32]
+                        self.get_square_coordinates(piece_move.get_to_pos())
                     )
                 }
             }
@@ -167,37 +1417,69 @@ impl Scene for Game {
 
 
 
-        if self.is_checked {
-            let king_pos = self.board.find_piece(self.current_player, PieceType::King);
+        if let Some(checked_player) = self.get_displayed_check() {
+            let king_pos = board.find_piece(checked_player, PieceType::King);
 
-            canvas.draw(assets.graphics.get("checkhighlight").unwrap(), [(32*king_pos.column+16) as f32, (32*king_pos.row+16) as f32]);
+            canvas.draw(assets.graphics.get("checkhighlight").unwrap(), self.get_square_coordinates(king_pos));
         }
 
-        for (col_index, col) in self.board.state.iter().enumerate() {
+        let animation = self.animation.as_ref().filter(|_| self.viewed_ply.is_none());
+
+        for (col_index, col) in board.state.iter().enumerate() {
             for (row_index, maybe_piece) in col.into_iter().enumerate() {
                 if let Some(piece) = maybe_piece {
-                    let piece_asset_type = match piece.get_piece_type() {
-                        PieceType::Pawn => "pawn",
-                        PieceType::Knight => "knight",
-                        PieceType::Bishop => "bishop",
-                        PieceType::Rook => "rook",
-                        PieceType::Queen => "queen",
-                        PieceType::King => "king",
-                    };
-
-                    let piece_asset_player = match piece.get_color() {
-                        Player::Black => "black",
-                        Player::White => "white",
-                    };
-
-                    let piece_asset_id = String::from(piece_asset_player) + piece_asset_type;
-
-                    canvas.draw(assets.graphics.get(&piece_asset_id).unwrap(), [(32*col_index+16) as f32, (32*row_index+16) as f32]);
+                    let pos = Position::new(col_index as i32, row_index as i32);
+
+                    if animation.is_some_and(|animation| animation.hidden_positions.contains(&pos)) {
+                        continue;
+                    }
+
+                    let piece_asset_id = Game::get_piece_asset_id(piece.get_color(), piece.get_piece_type());
+
+                    canvas.draw(
+                        assets.graphics.get(&piece_asset_id).unwrap(),
+                        self.get_square_coordinates(pos)
+                    );
                 }
             }
         }
 
-        
+        if let Some(animation) = animation {
+            self.draw_animation(animation, assets, canvas);
+        }
+
+        self.draw_annotations(canvas, ctx);
+
+        if self.modals.is_none() {
+            self.draw_hover(assets, canvas, ctx);
+        }
+
+        self.move_list.draw(&self.history, self.viewed_ply, canvas, ctx);
+        self.draw_analysis(canvas, ctx);
+
+        if let Some(evaluations) = &self.evaluations {
+            let ply = self.viewed_ply.unwrap_or(self.history.len());
+
+            if self.eval_graph.is_visible {
+                self.eval_graph.draw(evaluations, ply, canvas, ctx);
+            }
+
+            if let Some(review_panel) = &self.review_panel {
+                if let Some((from_pos, to_pos)) = ReviewPanel::get_better_move(evaluations, ply) {
+                    self.draw_arrow(from_pos, to_pos, Color::new(0.1, 0.7, 0.2, 0.7), canvas, ctx);
+                }
+
+                review_panel.draw(evaluations, &self.history, self.get_position(ply.saturating_sub(1)), ply, canvas, ctx);
+            }
+        }
+
+        if let (Some(network_panel), Some(network)) = (&self.network_panel, &self.network) {
+            network_panel.draw(network, canvas, ctx);
+        }
+
+        self.draw_material(assets, canvas, ctx);
+        self.draw_clocks(canvas, ctx);
+        self.draw_prompt(canvas);
 
         if let Some(modal) = self.modals.as_ref() {
             canvas.draw(&graphics::Mesh::new_rectangle(
@@ -211,7 +1493,17 @@ impl Scene for Game {
         }
     }
 
-    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, release_data: Option<(Vec2, MouseButton)>, key_press: Option<KeyCode>, text_input: &str, ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        self.update_network(ctx);
+
+        if let Some(animation) = self.animation.as_mut() {
+            animation.update(ctx.time.delta().as_secs_f32());
+
+            if animation.is_finished() || press_data.is_some() || key_press.is_some() {
+                self.animation = None;
+            }
+        }
+
         if let Some(game_modal) = &self.modals {
             if let Some(message) = game_modal.check_for_message(press_data.map(|(pos, _)| pos)) {
                 self.game_events.push(message);
@@ -222,111 +1514,298 @@ impl Scene for Game {
             let events: Vec<GameEvent> = self.game_events.drain(..).collect();
             for event in events {
                 match event {
-                    GameEvent::Replay => app_events.push(AppEvent::OpenNewGame),
+                    // a network game needs both players to set up a new one
+                    GameEvent::Replay if self.network.is_some() => app_events.push(AppEvent::OpenNewGame),
+                    GameEvent::Replay => app_events.push(AppEvent::StartGame(self.config.clone())),
                     GameEvent::Exit => app_events.push(AppEvent::OpenMainMenu),
+                    GameEvent::Review => self.start_review(ctx),
+                    GameEvent::ToggleGraph => {
+                        self.eval_graph.is_visible = !self.eval_graph.is_visible;
+
+                        if self.eval_graph.is_visible {
+                            self.start_evaluations();
+                        }
+
+                        self.update_move_list_layout();
+                    },
+                    GameEvent::CloseReview => {
+                        self.review_panel = None;
+                        self.update_move_list_layout();
+
+                        if let Some(end_type) = self.end_type.clone() {
+                            self.end_game(end_type, ctx);
+                        }
+                    },
+                    GameEvent::OfferDraw | GameEvent::Resign if self.end_type.is_some() => (),
+                    GameEvent::OfferDraw => {
+                        let Some(network) = self.network.as_mut() else {
+                            continue;
+                        };
+
+                        if network.draw_offer == DrawOffer::Received {
+                            network.draw_offer = DrawOffer::None;
+                            network.send(NetMessage::AnswerDraw { accepted: true });
+
+                            self.end_game(EndType::Agreement, ctx);
+                        }
+                        else {
+                            network.draw_offer = DrawOffer::Sent;
+                            network.send(NetMessage::OfferDraw);
+                        }
+                    },
+                    GameEvent::Resign => {
+                        if let Some(network) = self.network.as_mut() {
+                            network.send(NetMessage::Resign);
+                        }
+
+                        self.end_game(EndType::Resignation(self.config.player_color.get_opponent()), ctx);
+                    },
+                    GameEvent::SendChat(text) => {
+                        if let Some(network) = self.network.as_mut() {
+                            network.send_chat(text);
+                        }
+                    },
+                    GameEvent::SaveGame => {
+                        self.save_result = Some(self.to_saved_game().save()
+                            .map(|path| path.file_name().unwrap_or_default().to_string_lossy().into_owned()));
+
+                        if let Some(end_type) = self.end_type.clone() {
+                            self.end_game(end_type, ctx);
+                        }
+                    },
                     GameEvent::ChoosePiece(piece_type, position) => {
                         self.modals = None;
                         
-                        let old_piece = self.board[position].clone().unwrap();
-    
-                        self.board[position] = match piece_type {
-                            PieceType::Knight => Some(Box::new(Knight::new(old_piece.get_color()))),
-                            PieceType::Bishop => Some(Box::new(Bishop::new(old_piece.get_color()))),
-                            PieceType::Rook => Some(Box::new(Rook::new(old_piece.get_color()))),
-                            PieceType::Queen => Some(Box::new(Queen::new(old_piece.get_color()))),
-                            _ => None,
-                        };
-                        
+                        self.board.promote(position, piece_type);
+
+                        let player = self.board[position].as_ref().unwrap().get_color();
+                        self.start_animation(Animation::from_promotion(position, player, piece_type, self.animation_duration));
+
+                        self.pending_sound = Some(SoundEffect::Promotion);
+
+                        if let Some((board_before, piece_move)) = self.pending_promotion.take() {
+                            self.record_move(&board_before, piece_move.as_ref(), Some(piece_type));
+                        }
+
                         self.game_events.push(GameEvent::CheckForGameEnd);
                     },
                     GameEvent::CheckForGameEnd => {
                         println!("{:?}", self.get_total_pieces());
                         println!("{:?}", self.fifty_move_rule);
 
-                        let snapshot = self.board.get_snapshot();
-
-                        if let Some(count) = self.snapshots.get_mut(&snapshot) {
-                            *count += 1;
-                        }
-                        else {
-                            self.snapshots.insert(snapshot.clone(), 1);
-                        }
-
-                        if *self.snapshots.get(&snapshot).unwrap() == 3 {
-                            self.modals = Some(Box::new(EndgameModal::new(EndType::Repetition, ctx)));
+                        if self.record_snapshot() == 3 {
+                            self.end_game(EndType::Repetition, ctx);
                         }
 
                         self.is_checked = self.board.get_check(self.current_player);
 
                         if self.is_checkmate_or_stalemate() {
                             if self.is_checked {
-                                self.modals = Some(Box::new(EndgameModal::new(EndType::Checkmate(self.current_player.get_opponent()), ctx)));
+                                self.end_game(EndType::Checkmate(self.current_player.get_opponent()), ctx);
                             }
                             else {
-                                self.modals = Some(Box::new(EndgameModal::new(EndType::Stalemate, ctx)));
+                                self.end_game(EndType::Stalemate, ctx);
                             }
                         }
 
                         if self.is_insufficient_material() {
-                            self.modals = Some(Box::new(EndgameModal::new(EndType::InsufficientMaterial, ctx)));
+                            self.end_game(EndType::InsufficientMaterial, ctx);
                         }
 
                         if self.fifty_move_rule >= 100 {
-                            self.modals = Some(Box::new(EndgameModal::new(EndType::FiftyMoveRule, ctx)));
+                            self.end_game(EndType::FiftyMoveRule, ctx);
+                        }
+
+                        let sound = if self.modals.is_some() {
+                            Some(SoundEffect::GameEnd)
+                        } else if self.is_checked {
+                            Some(SoundEffect::Check)
+                        } else {
+                            self.pending_sound
+                        };
+
+                        if let Some(sound) = sound {
+                            app_events.push(AppEvent::PlaySound(sound));
+                        }
+
+                        if self.modals.is_some() {
+                            SavedGame::delete_autosave();
+                        }
+                        else if !self.is_puzzle && self.network.is_none() {
+                            self.to_saved_game().write_autosave();
+                        }
+
+                        self.pending_sound = None;
+                    },
+                    GameEvent::ShowFirstMove => {
+                        if !self.history.is_empty() {
+                            self.viewed_ply = Some(0);
+                        }
+                    },
+                    GameEvent::ShowPreviousMove => {
+                        let ply = self.viewed_ply.unwrap_or(self.history.len());
+
+                        if ply > 0 {
+                            self.viewed_ply = Some(ply - 1);
+                        }
+                    },
+                    GameEvent::ShowNextMove => {
+                        if let Some(ply) = self.viewed_ply {
+                            self.show_ply(ply + 1);
                         }
                     },
+                    GameEvent::ShowLastMove => self.viewed_ply = None,
+                    GameEvent::ShowMove(index) => self.show_ply(index + 1),
+                }
+            }
+
+            let press_position = press_data.map(|(pos, _)| pos);
+
+            if let (Some(network_panel), Some(network)) = (self.network_panel.as_mut(), self.network.as_ref()) {
+                if let Some(message) = network_panel.update(press_position, key_press, text_input, network) {
+                    self.game_events.push(message);
+                }
+            }
+
+            // typing in the chat or typing a move must not trigger shortcuts
+            let mut is_typing = self.network_panel.as_ref().is_some_and(|network_panel| network_panel.is_typing());
+
+            if !is_typing && self.end_type.is_none() && self.viewed_ply.is_none() {
+                is_typing = self.controllers[Self::get_controller_index(self.current_player)].handle_input(key_press, text_input);
+            }
+
+            match key_press.filter(|_| !is_typing) {
+                Some(KeyCode::Left) => self.game_events.push(GameEvent::ShowPreviousMove),
+                Some(KeyCode::Right) => self.game_events.push(GameEvent::ShowNextMove),
+                Some(KeyCode::Up) => self.game_events.push(GameEvent::ShowFirstMove),
+                Some(KeyCode::Down) => self.game_events.push(GameEvent::ShowLastMove),
+                Some(KeyCode::S) => self.game_events.push(GameEvent::SaveGame),
+                Some(KeyCode::G) => self.game_events.push(GameEvent::ToggleGraph),
+                Some(KeyCode::T) => self.show_threats = !self.show_threats,
+                _ => (),
+            }
+
+            if let Some(message) = self.move_list.check_for_message(press_position, self.history.len(), self.viewed_ply) {
+                self.game_events.push(message);
+            }
+
+            if let Some(evaluations) = self.evaluations.as_mut() {
+                evaluations.poll();
+
+                let ply = self.viewed_ply.unwrap_or(self.history.len());
+
+                if let Some(message) = self.review_panel.as_ref().and_then(|review_panel| review_panel.check_for_message(press_position, evaluations, ply)) {
+                    self.game_events.push(message);
+                }
+
+                if let Some(message) = self.eval_graph.is_visible.then(|| self.eval_graph.check_for_message(press_position, evaluations)).flatten() {
+                    self.game_events.push(message);
+                }
+            }
+
+            if let Some((press_position, MouseButton::Left)) = press_data {
+                if let Some(line) = self.get_analysis_line_at(press_position) {
+                    self.toggle_preview(line);
+                }
+                else if self.preview.is_some() && self.get_square_at(press_position).is_some() {
+                    self.preview = None;
+
+                    return;
+                }
+            }
+
+            if let Some((press_position, button)) = press_data {
+                if button == MouseButton::Right {
+                    self.annotation_start = self.get_square_at(press_position);
                 }
+                else if button == MouseButton::Left && self.get_square_at(press_position).is_some() {
+                    self.annotations.remove(&self.get_displayed_ply());
+                }
+            }
+
+            if let Some((release_position, MouseButton::Right)) = release_data {
+                let from_pos = self.annotation_start.take();
+                let to_pos = self.get_square_at(release_position);
+
+                if let (Some(from_pos), Some(to_pos)) = (from_pos, to_pos) {
+                    let annotation_color = AnnotationColor::from_mods(ctx.keyboard.active_mods());
+                    let annotations = self.annotations.entry(self.get_displayed_ply()).or_default();
+
+                    if from_pos == to_pos {
+                        annotations.toggle_mark(to_pos, annotation_color);
+                    }
+                    else {
+                        annotations.toggle_arrow(from_pos, to_pos, annotation_color);
+                    }
+                }
+            }
+
+            let is_waiting_for_peer = self.network.as_ref().is_some_and(|network| !network.is_ready);
+
+            if self.end_type.is_none() && self.pending_promotion.is_none() && !is_waiting_for_peer {
+                self.update_clock(ctx, app_events);
+            }
+
+            if self.end_type.is_none() && self.pending_promotion.is_none() && self.modals.is_none() && self.game_events.is_empty() && self.animation.is_none() {
+                self.update_controller(ctx);
+            }
+
+            self.update_analysis();
+
+            if self.viewed_ply.is_some() || self.preview.is_some() || self.end_type.is_some() || is_waiting_for_peer {
+                return;
+            }
+
+            if !self.premoves.is_empty() && self.is_human_player(self.current_player) && self.game_events.is_empty() {
+                self.play_premove(ctx);
             }
-            
 
             if let Some((press_position, button)) = press_data {
                 if button == MouseButton::Right {
                     self.legal_moves = None;
+                    self.selected_position = None;
+                    self.premoves.clear();
+                    self.pending_confirmation = None;
                 }
     
-                if press_position[0] > 16. && press_position[0] < 272. 
-                && press_position[1] > 16. && press_position[1] < 272. {
-                    let column = ((press_position[0] - 16.) / 32.) as usize;
-                    let row = ((press_position[1] - 16.) / 32.) as usize;
-    
-                    let pos = Position::new(column as i32, row as i32);
-                    let piece = self.board[pos].clone();
+                if let Some(pos) = self.get_square_at(press_position) {
+                    if let Some(piece_move) = self.pending_confirmation.take() {
+                        if piece_move.get_to_pos() == pos {
+                            self.submit_move(piece_move.as_ref(), None, ctx);
+                        }
+
+                        return;
+                    }
+
+                    let is_premove = !self.is_human_player(self.current_player);
+                    let piece = if is_premove { self.get_premove_board()[pos].clone() } else { self.board[pos].clone() };
     
                     if let Some(legal_moves) = self.legal_moves.clone() {
                         for piece_move in legal_moves {
                             if piece_move.get_to_pos() == pos {
-                                self.fifty_move_rule += 1;
-                                if self.board[piece_move.get_to_pos()].is_some()
-                                || self.board[piece_move.get_from_pos()].clone().unwrap().get_piece_type() == PieceType::Pawn {
-                                    self.fifty_move_rule = 0;
+                                if is_premove {
+                                    self.premoves.push(piece_move);
                                 }
-                                
-                                piece_move.execute(&mut self.board);
-
-                                if piece_move.is_pawn_promotion_move() {
-                                    self.modals = Some(Box::new(PawnPromotionModal::new(ctx, piece_move.get_to_pos())) as Box<dyn Modal>)
+                                else if self.confirm_moves {
+                                    self.pending_confirmation = Some(piece_move);
+                                }
+                                else {
+                                    self.submit_move(piece_move.as_ref(), None, ctx);
                                 }
-                                
-                                self.legal_moves = None;
-                                self.current_player = self.current_player.get_opponent();
 
-                                self.game_events.push(GameEvent::CheckForGameEnd);
+                                self.legal_moves = None;
+                                self.selected_position = None;
                             }
                         }
                     } else {
                         if let Some(piece) = piece {
-                            if self.current_player == piece.get_color() {
-                                let mut piece_moves = piece.get_moves(pos, &self.board);
-    
-                                piece_moves.retain(|piece_move| {
-                                    let mut test_board = self.board.clone();
-                                    
-                                    piece_move.execute(&mut test_board);
-    
-                                    !test_board.get_check(self.current_player)
-                                });
-    
-                                self.legal_moves = Some(piece_moves);
+                            if is_premove && self.is_human_player(piece.get_color()) {
+                                self.legal_moves = Some(self.get_premove_moves(pos));
+                                self.selected_position = Some(pos);
+                            }
+                            else if !is_premove && self.current_player == piece.get_color() {
+                                self.legal_moves = Some(self.board.get_legal_moves_from(pos));
+                                self.selected_position = Some(pos);
                             }
                         }
                     }
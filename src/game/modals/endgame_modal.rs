@@ -7,15 +7,21 @@ use super::{Modal, GameEvent};
 pub struct EndgameModal {
     end_type: EndType,
     replay_button: Button,
-    exit_button: Button
+    save_button: Button,
+    exit_button: Button,
+    review_button: Button,
 }
 
+#[derive(Clone)]
 pub enum EndType {
     Checkmate(Player),
+    Timeout(Player),
+    Resignation(Player),
     Stalemate,
     InsufficientMaterial,
     Repetition,
-    FiftyMoveRule
+    FiftyMoveRule,
+    Agreement,
 }
 
 
@@ -25,7 +31,7 @@ impl EndgameModal {
             graphics::Mesh::new_rectangle(
                 ctx,
                 graphics::DrawMode::fill(),
-                Rect::new(0., 0., 100., 30.),
+                Rect::new(0., 0., 80., 30.),
                 Color::GREEN
             ).unwrap(),
             Text::new(TextFragment {
@@ -34,7 +40,24 @@ impl EndgameModal {
                 color: Some(Color::BLACK),
                 ..Default::default()
             }),
-            [39., 152.].into(),
+            [20., 130.].into(),
+            ctx
+        );
+
+        let save_button = Button::new(
+            graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(0., 0., 80., 30.),
+                Color::GREEN
+            ).unwrap(),
+            Text::new(TextFragment {
+                text: "Save".into(),
+                scale: Some(PxScale::from(16.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            }),
+            [104., 130.].into(),
             ctx
         );
 
@@ -42,7 +65,7 @@ impl EndgameModal {
             graphics::Mesh::new_rectangle(
                 ctx,
                 graphics::DrawMode::fill(),
-                Rect::new(0., 0., 100., 30.),
+                Rect::new(0., 0., 80., 30.),
                 Color::GREEN
             ).unwrap(),
             Text::new(TextFragment {
@@ -51,14 +74,33 @@ impl EndgameModal {
                 color: Some(Color::BLACK),
                 ..Default::default()
             }),
-            [149., 152.].into(),
+            [188., 130.].into(),
+            ctx
+        );
+
+        let review_button = Button::new(
+            graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(0., 0., 80., 30.),
+                Color::GREEN
+            ).unwrap(),
+            Text::new(TextFragment {
+                text: "Review".into(),
+                scale: Some(PxScale::from(16.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            }),
+            [104., 166.].into(),
             ctx
         );
 
         Self {
             end_type,
             replay_button,
-            exit_button
+            save_button,
+            exit_button,
+            review_button,
         }
     }
 }
@@ -69,16 +111,19 @@ impl Modal for EndgameModal {
             &graphics::Mesh::new_rectangle(
                 ctx,
                 graphics::DrawMode::fill(),
-                Rect::new(0., 0., 256., 96.),
+                Rect::new(0., 0., 256., 128.),
                 Color::WHITE
-        ).unwrap(), [288./2. - 256./2., 288./2. - 96./2.]);
+        ).unwrap(), [288./2. - 256./2., 288./2. - 128./2.]);
 
         let win_text = match self.end_type {
             EndType::Checkmate(player) => if player == Player::White { "White Won by checkmate" } else { "Black Won by checkmate" },
+            EndType::Timeout(player) => if player == Player::White { "White Won on time" } else { "Black Won on time" },
+            EndType::Resignation(player) => if player == Player::White { "White Won by resignation" } else { "Black Won by resignation" },
             EndType::Stalemate => "Draw by Stalemate",
             EndType::InsufficientMaterial => "Draw by Insufficient Material",
             EndType::Repetition => "Draw by Repetition",
             EndType::FiftyMoveRule => "Draw by 50 Move Rule",
+            EndType::Agreement => "Draw by Agreement",
         };
 
         let mut text = Text::new(TextFragment {
@@ -96,21 +141,29 @@ impl Modal for EndgameModal {
 
         canvas.draw(
             &text,
-            [288./2. - text_dimensions.w/2., 288./2. - 96./2. + 10.]
+            [288./2. - text_dimensions.w/2., 288./2. - 128./2. + 10.]
         );
 
 
         self.replay_button.draw(canvas, ctx);
+        self.save_button.draw(canvas, ctx);
         self.exit_button.draw(canvas, ctx);
+        self.review_button.draw(canvas, ctx);
     }
 
     fn check_for_message(&self, press_position: Option<Vec2>) -> Option<GameEvent> {
         if self.replay_button.is_clicked(press_position) {
             return Some(GameEvent::Replay);
         }
+        if self.save_button.is_clicked(press_position) {
+            return Some(GameEvent::SaveGame);
+        }
         if self.exit_button.is_clicked(press_position) {
             return Some(GameEvent::Exit);
         }
+        if self.review_button.is_clicked(press_position) {
+            return Some(GameEvent::Review);
+        }
         
         None
     }
@@ -0,0 +1,163 @@
+use ggez::{glam::Vec2, graphics::{self, Canvas, Color, DrawParam, PxScale, Rect, Text, TextFragment}, Context};
+
+use crate::widgets::Button;
+
+use super::{game_event::GameEvent, history::HistoryEntry, piece::Player};
+
+const PANEL_X: f32 = 288.;
+const PANEL_WIDTH: f32 = 160.;
+const ROW_HEIGHT: f32 = 16.;
+const WHITE_MOVE_X: f32 = PANEL_X + 30.;
+const BLACK_MOVE_X: f32 = PANEL_X + 92.;
+const MOVE_WIDTH: f32 = 60.;
+
+pub struct MoveList {
+    first_button: Button,
+    previous_button: Button,
+    next_button: Button,
+    last_button: Button,
+    list_y: f32,
+    visible_rows: usize,
+    // games from a fen can start with black to move and at any move number
+    first_ply: usize,
+    first_move_number: u32,
+}
+
+impl MoveList {
+    pub fn new(ctx: &mut Context, start_player: Player, start_fullmove: u32) -> Self {
+        let mut buttons = ["<<", "<", ">", ">>"].into_iter().enumerate().map(|(i, label)| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 36., 20.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: label.into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [PANEL_X + 4. + 38. * i as f32, 4.].into(),
+                ctx
+            )
+        }).collect::<Vec<Button>>().into_iter();
+
+        Self {
+            first_button: buttons.next().unwrap(),
+            previous_button: buttons.next().unwrap(),
+            next_button: buttons.next().unwrap(),
+            last_button: buttons.next().unwrap(),
+            list_y: 30.,
+            visible_rows: 13,
+            first_ply: if start_player == Player::Black { 1 } else { 0 },
+            first_move_number: start_fullmove,
+        }
+    }
+
+    pub fn set_layout(&mut self, list_y: f32, visible_rows: usize) {
+        self.list_y = list_y;
+        self.visible_rows = visible_rows;
+    }
+
+    fn get_highlighted_move(history_len: usize, viewed_ply: Option<usize>) -> Option<usize> {
+        match viewed_ply {
+            Some(ply) => ply.checked_sub(1),
+            None => history_len.checked_sub(1),
+        }
+    }
+
+    fn get_first_row(&self, history_len: usize, viewed_ply: Option<usize>) -> usize {
+        let focus_row = Self::get_highlighted_move(history_len, viewed_ply).map(|index| (self.first_ply + index) / 2).unwrap_or(0);
+
+        if focus_row >= self.visible_rows {
+            return focus_row + 1 - self.visible_rows;
+        }
+
+        0
+    }
+
+    pub fn draw(&self, history: &[HistoryEntry], viewed_ply: Option<usize>, canvas: &mut Canvas, ctx: &mut Context) {
+        canvas.draw(&graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0., 0., PANEL_WIDTH, 288.),
+            Color::from_rgb(235, 237, 233)
+        ).unwrap(), [PANEL_X, 0.]);
+
+        self.first_button.draw(canvas, ctx);
+        self.previous_button.draw(canvas, ctx);
+        self.next_button.draw(canvas, ctx);
+        self.last_button.draw(canvas, ctx);
+
+        let highlighted_move = Self::get_highlighted_move(history.len(), viewed_ply);
+        let first_row = self.get_first_row(history.len(), viewed_ply);
+
+        let rows = (self.first_ply + history.len()).div_ceil(2);
+
+        for row in (first_row..rows).take(self.visible_rows) {
+            let y = self.list_y + ROW_HEIGHT * (row - first_row) as f32;
+            let move_number = self.first_move_number + row as u32;
+
+            // a game that starts with black to move leaves the first white cell empty
+            let mut number_text = Text::new(if row == 0 && self.first_ply == 1 { format!("{}...", move_number) } else { format!("{}.", move_number) });
+            number_text.set_scale(14.);
+
+            canvas.draw(&number_text, DrawParam::new().dest([PANEL_X + 4., y]).color(Color::BLACK));
+
+            for column in 0..2 {
+                let Some(index) = (row * 2 + column).checked_sub(self.first_ply).filter(|index| *index < history.len()) else {
+                    continue;
+                };
+
+                let entry = &history[index];
+                let x = if column == 0 { WHITE_MOVE_X } else { BLACK_MOVE_X };
+
+                if highlighted_move == Some(index) {
+                    canvas.draw(&graphics::Mesh::new_rectangle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        Rect::new(0., 0., MOVE_WIDTH, ROW_HEIGHT),
+                        Color::from_rgb(199, 207, 204)
+                    ).unwrap(), [x - 2., y]);
+                }
+
+                let mut san_text = Text::new(entry.san.as_str());
+                san_text.set_scale(14.);
+
+                canvas.draw(&san_text, DrawParam::new().dest([x, y]).color(Color::BLACK));
+            }
+        }
+    }
+
+    pub fn check_for_message(&self, press_position: Option<Vec2>, history_len: usize, viewed_ply: Option<usize>) -> Option<GameEvent> {
+        if self.first_button.is_clicked(press_position) {
+            return Some(GameEvent::ShowFirstMove);
+        }
+        if self.previous_button.is_clicked(press_position) {
+            return Some(GameEvent::ShowPreviousMove);
+        }
+        if self.next_button.is_clicked(press_position) {
+            return Some(GameEvent::ShowNextMove);
+        }
+        if self.last_button.is_clicked(press_position) {
+            return Some(GameEvent::ShowLastMove);
+        }
+
+        if let Some(position) = press_position {
+            if position.x > WHITE_MOVE_X - 2. && position.x < BLACK_MOVE_X - 2. + MOVE_WIDTH
+            && position.y > self.list_y && position.y < self.list_y + ROW_HEIGHT * self.visible_rows as f32 {
+                let row = self.get_first_row(history_len, viewed_ply) + ((position.y - self.list_y) / ROW_HEIGHT) as usize;
+                let column = if position.x < BLACK_MOVE_X - 2. { 0 } else { 1 };
+                let index = (row * 2 + column).checked_sub(self.first_ply);
+
+                if let Some(index) = index.filter(|index| *index < history_len) {
+                    return Some(GameEvent::ShowMove(index));
+                }
+            }
+        }
+
+        None
+    }
+}
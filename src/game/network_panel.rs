@@ -0,0 +1,98 @@
+use ggez::{glam::Vec2, graphics::{self, Canvas, Color, DrawParam, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+
+use crate::{network::{DrawOffer, NetSession}, widgets::{Button, TextField}};
+
+use super::game_event::GameEvent;
+
+const PANEL_X: f32 = 292.;
+const PANEL_Y: f32 = 130.;
+const CHAT_LINES: usize = 3;
+
+pub struct NetworkPanel {
+    chat_field: TextField,
+    draw_button: Button,
+    resign_button: Button,
+}
+
+impl NetworkPanel {
+    pub fn new(ctx: &mut Context) -> Self {
+        let mut buttons = [("Offer draw", 0.), ("Resign", 78.)].into_iter().map(|(label, x)| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 74., 20.),
+                    Color::GREEN
+                ).unwrap(),
+                Self::get_label(label),
+                [PANEL_X + x, 218.].into(),
+                ctx
+            )
+        }).collect::<Vec<Button>>().into_iter();
+
+        Self {
+            chat_field: TextField::new("", [PANEL_X, 194.].into(), 152.),
+            draw_button: buttons.next().unwrap(),
+            resign_button: buttons.next().unwrap(),
+        }
+    }
+
+    fn get_label(label: &str) -> Text {
+        Text::new(TextFragment {
+            text: label.into(),
+            scale: Some(PxScale::from(12.0)),
+            color: Some(Color::BLACK),
+            ..Default::default()
+        })
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.chat_field.is_focused
+    }
+
+    pub fn draw(&self, session: &NetSession, canvas: &mut Canvas, ctx: &mut Context) {
+        let chat_start = session.chat.len().saturating_sub(CHAT_LINES);
+        let lines = std::iter::once(&session.status).chain(&session.chat[chat_start..]);
+
+        for (i, line) in lines.enumerate() {
+            let mut line_text = Text::new(line.as_str());
+            line_text.set_scale(12.);
+            line_text.set_bounds([152., 14.]);
+
+            let color = if i == 0 { Color::from_rgb(30, 110, 230) } else { Color::BLACK };
+
+            canvas.draw(&line_text, DrawParam::new().dest([PANEL_X, PANEL_Y + 16. * i as f32]).color(color));
+        }
+
+        self.chat_field.draw(canvas, ctx);
+        self.draw_button.draw(canvas, ctx);
+        self.resign_button.draw(canvas, ctx);
+    }
+
+    pub fn update(&mut self, press_position: Option<Vec2>, key_press: Option<KeyCode>, text_input: &str, session: &NetSession) -> Option<GameEvent> {
+        self.draw_button.text = Self::get_label(match session.draw_offer {
+            DrawOffer::None => "Offer draw",
+            DrawOffer::Sent => "Draw offered",
+            DrawOffer::Received => "Accept draw",
+        });
+
+        self.chat_field.update(press_position, text_input, key_press);
+
+        if self.chat_field.is_focused && key_press == Some(KeyCode::Return) && !self.chat_field.text.trim().is_empty() {
+            let text = self.chat_field.text.trim().to_string();
+            self.chat_field.text.clear();
+
+            return Some(GameEvent::SendChat(text));
+        }
+
+        if self.draw_button.is_clicked(press_position) && session.draw_offer != DrawOffer::Sent {
+            return Some(GameEvent::OfferDraw);
+        }
+
+        if self.resign_button.is_clicked(press_position) {
+            return Some(GameEvent::Resign);
+        }
+
+        None
+    }
+}
@@ -0,0 +1,161 @@
+use super::{board::Board, piece::{PieceType, Player}, piece_move::ChessMove, position::Position};
+
+pub fn get_uci(from_pos: Position, to_pos: Position, promotion: Option<PieceType>) -> String {
+    let mut uci = from_pos.to_algebraic() + &to_pos.to_algebraic();
+
+    if let Some(piece_type) = promotion {
+        uci.push(piece_type.get_letter().to_ascii_lowercase());
+    }
+
+    uci
+}
+
+pub fn parse_uci(uci: &str) -> Option<(Position, Position, Option<PieceType>)> {
+    if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+        return None;
+    }
+
+    let from_pos = Position::from_algebraic(&uci[0..2])?;
+    let to_pos = Position::from_algebraic(&uci[2..4])?;
+    let promotion = match uci[4..].chars().next() {
+        Some(letter) => Some(PieceType::from_letter(letter)?),
+        None => None,
+    };
+
+    Some((from_pos, to_pos, promotion))
+}
+
+// converts uci moves to san, stopping at the first illegal move
+pub fn get_san_line(board: &Board, player: Player, uci_moves: &[String]) -> Vec<String> {
+    let mut board = board.clone();
+    let mut player = player;
+    let mut san_moves = Vec::new();
+
+    for uci in uci_moves {
+        let Some(san) = play_uci(&mut board, player, uci) else {
+            break;
+        };
+
+        san_moves.push(san);
+        player = player.get_opponent();
+    }
+
+    san_moves
+}
+
+// plays a uci move on the board and returns its san, or None if it is not legal
+pub fn play_uci(board: &mut Board, player: Player, uci: &str) -> Option<String> {
+    let (from_pos, to_pos, promotion) = parse_uci(uci)?;
+    let piece_move = board.find_move(player, from_pos, to_pos)?;
+    let promotion = promotion.or(piece_move.is_pawn_promotion_move().then_some(PieceType::Queen));
+
+    let san = get_san(board, piece_move.as_ref(), promotion);
+
+    piece_move.execute(board);
+
+    if let Some(piece_type) = promotion {
+        board.promote(piece_move.get_to_pos(), piece_type);
+    }
+
+    Some(san)
+}
+
+// finds the legal move written in san and returns its uci, ignoring check marks and annotations
+pub fn parse_san(board: &Board, player: Player, san: &str) -> Option<String> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']).replace('0', "O");
+
+    for piece_move in board.get_legal_moves(player) {
+        let promotions = if piece_move.is_pawn_promotion_move() {
+            vec![Some(PieceType::Queen), Some(PieceType::Rook), Some(PieceType::Bishop), Some(PieceType::Knight)]
+        } else {
+            vec![None]
+        };
+
+        for promotion in promotions {
+            if get_san(board, piece_move.as_ref(), promotion).trim_end_matches(['+', '#']) == san {
+                return Some(get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion));
+            }
+        }
+    }
+
+    None
+}
+
+pub fn get_san(board: &Board, piece_move: &dyn ChessMove, promotion: Option<PieceType>) -> String {
+    let from_pos = piece_move.get_from_pos();
+    let to_pos = piece_move.get_to_pos();
+    let piece = board[from_pos].as_ref().unwrap();
+    let player = piece.get_color();
+
+    let mut san = String::new();
+
+    if piece_move.is_castle_move() {
+        san += if piece_move.get_king_to().column == 6 { "O-O" } else { "O-O-O" };
+    }
+    else {
+        let is_capture = board[to_pos].is_some() || Some(to_pos) == board.en_passant && piece.get_piece_type() == PieceType::Pawn;
+
+        if piece.get_piece_type() == PieceType::Pawn {
+            if is_capture {
+                san += &from_pos.to_algebraic()[..1];
+            }
+        }
+        else {
+            san.push(piece.get_piece_type().get_letter());
+
+            let ambiguous_moves: Vec<Box<dyn ChessMove>> = board.get_legal_moves(player)
+                .into_iter()
+                .filter(|other_move| {
+                    other_move.get_to_pos() == to_pos
+                    && other_move.get_from_pos() != from_pos
+                    && board[other_move.get_from_pos()].as_ref().unwrap().get_piece_type() == piece.get_piece_type()
+                })
+                .collect();
+
+            if !ambiguous_moves.is_empty() {
+                let same_column = ambiguous_moves.iter().any(|other_move| other_move.get_from_pos().column == from_pos.column);
+                let same_row = ambiguous_moves.iter().any(|other_move| other_move.get_from_pos().row == from_pos.row);
+
+                if !same_column {
+                    san += &from_pos.to_algebraic()[..1];
+                }
+                else if !same_row {
+                    san += &from_pos.to_algebraic()[1..];
+                }
+                else {
+                    san += &from_pos.to_algebraic();
+                }
+            }
+        }
+
+        if is_capture {
+            san += "x";
+        }
+
+        san += &to_pos.to_algebraic();
+
+        if let Some(piece_type) = promotion {
+            san.push('=');
+            san.push(piece_type.get_letter());
+        }
+    }
+
+    let mut test_board = board.clone();
+
+    piece_move.execute(&mut test_board);
+
+    if let Some(piece_type) = promotion {
+        test_board.promote(to_pos, piece_type);
+    }
+
+    if test_board.get_check(player.get_opponent()) {
+        if test_board.get_legal_moves(player.get_opponent()).is_empty() {
+            san += "#";
+        }
+        else {
+            san += "+";
+        }
+    }
+
+    san
+}
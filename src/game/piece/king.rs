@@ -39,6 +39,44 @@ impl King {
 
         moves
     }
+
+    pub fn can_castle(&self, board: &Board, castle_move: &CastleMove) -> bool {
+        let row = castle_move.from_pos.row;
+        let (rook_from, rook_to) = (castle_move.rook_from, castle_move.rook_to);
+        let king_to = castle_move.king_to;
+
+        let mut test_board = board.clone();
+        test_board[castle_move.from_pos] = None;
+        test_board[rook_from] = None;
+
+        let columns = [castle_move.from_pos.column, king_to.column, rook_from.column, rook_to.column];
+
+        for column in *columns.iter().min().unwrap()..=*columns.iter().max().unwrap() {
+            if !test_board.is_empty(Position::new(column, row)) {
+                return false;
+            }
+        }
+
+        let (start, end) = if king_to.column > castle_move.from_pos.column {
+            (castle_move.from_pos.column, king_to.column)
+        } else {
+            (king_to.column, castle_move.from_pos.column)
+        };
+
+        for column in start..=end {
+            let mut king = Box::new(self.clone());
+            king.set_has_moved();
+
+            let mut king_board = test_board.clone();
+            king_board[Position::new(column, row)] = Some(king);
+
+            if king_board.get_check(self.player) {
+                return false;
+            }
+        }
+
+        true
+    }
 }
 
 impl ChessPiece for King {
@@ -58,80 +96,21 @@ impl ChessPiece for King {
         }
 
         if !self.has_moved {
-            let row = if self.player == Player::White {7} else {0};
-            
-            let piece = board.state[0][row].clone();
-
-            if let Some(piece) = piece {
-                if piece.get_piece_type() == PieceType::Rook 
-                && piece.get_color() == self.player {
-                    let mut empty = true;
-                    
-                    if !piece.get_has_moved() {
-                        for i in 0..3 {
-                            if !board.is_empty(from_position + (Direction::WEST * (i + 1)).into()) {
-                                empty = false;
-                                break;
-                            }
-
-                            let mut test_board = board.clone();
-                            let test_move = NormalMove::new(
-                                from_position,
-                                from_position + (Direction::WEST).into()
-                            );
-
-                            test_move.execute(&mut test_board);
-
-                            if test_board.get_check(self.player) {
-                                empty = false;
-                                break;
-                            }
-                        }
-
-                        if empty {
-                            moves.push(Box::new(CastleMove::new(
-                                from_position,
-                                from_position + (Direction::WEST * 2).into()
-                            )));
-                        }
-                    }
+            for column in 0..8 {
+                let rook_pos = Position::new(column, from_position.row);
+
+                let Some(rook) = &board[rook_pos] else {
+                    continue;
+                };
+
+                if rook.get_piece_type() != PieceType::Rook || rook.get_color() != self.player || rook.get_has_moved() {
+                    continue;
                 }
-            }
 
-            let piece = board.state[7][row].clone();
-
-            if let Some(piece) = piece {
-                if piece.get_piece_type() == PieceType::Rook 
-                && piece.get_color() == self.player {
-                    if !piece.get_has_moved() {
-                        let mut empty = true;
-                        for i in 0..2 {
-                            if !board.is_empty(from_position + (Direction::EAST * (i + 1)).into()) {
-                                empty = false;
-                                break;
-                            }
-
-                            let mut test_board = board.clone();
-                            let test_move = NormalMove::new(
-                                from_position,
-                                from_position + (Direction::EAST).into()
-                            );
-
-                            test_move.execute(&mut test_board);
-
-                            if test_board.get_check(self.player) {
-                                empty = false;
-                                break;
-                            }
-                        }
-
-                        if empty {
-                            moves.push(Box::new(CastleMove::new(
-                                from_position,
-                                from_position + (Direction::EAST * 2).into()
-                            )));
-                        }
-                    }
+                let castle_move = CastleMove::new(from_position, rook_pos);
+
+                if self.can_castle(board, &castle_move) {
+                    moves.push(Box::new(castle_move));
                 }
             }
         }
@@ -180,4 +159,59 @@ impl ChessPiece for King {
     fn set_has_moved(&mut self) {
         self.has_moved = true
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{board::Board, fen, piece::Player, position::Position};
+
+    fn square(name: &str) -> Position {
+        Position::from_algebraic(name).unwrap()
+    }
+
+    fn get_white_back_rank(board: &Board) -> String {
+        fen::get_fen(board, Player::White, 0, 1).split(['/', ' ']).nth(7).unwrap().into()
+    }
+
+    // castles by moving the king onto the rook, returns the white back rank afterwards
+    fn castle(fen: &str, king: &str, rook: &str) -> Option<String> {
+        let mut board = fen::parse_fen(fen).unwrap().board;
+        let castle_move = board.find_move(Player::White, square(king), square(rook))?;
+
+        assert!(castle_move.is_castle_move());
+
+        castle_move.execute(&mut board);
+
+        Some(get_white_back_rank(&board))
+    }
+
+    #[test]
+    fn castles_to_the_standard_squares_from_any_start() {
+        let fen = "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w GBgb - 0 1";
+
+        assert_eq!(castle(fen, "e1", "g1").as_deref(), Some("1R3RK1"));
+        assert_eq!(castle(fen, "e1", "b1").as_deref(), Some("2KR2R1"));
+    }
+
+    #[test]
+    fn castles_without_moving_the_king() {
+        assert_eq!(castle("6kr/8/8/8/8/8/8/6KR w Hh - 0 1", "g1", "h1").as_deref(), Some("5RK1"));
+    }
+
+    #[test]
+    fn castles_long_with_the_king_next_to_the_rook() {
+        assert_eq!(castle("4k3/8/8/8/8/8/8/RK6 w A - 0 1", "b1", "a1").as_deref(), Some("2KR4"));
+    }
+
+    #[test]
+    fn can_not_castle_through_pieces_or_attacks() {
+        // the rook's destination is taken
+        assert_eq!(castle("4k3/8/8/8/8/8/8/RK1N4 w A - 0 1", "b1", "a1"), None);
+        // the king passes an attacked square
+        assert_eq!(castle("4kr2/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "h1"), None);
+        // the king is in check
+        assert_eq!(castle("4r1k1/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "h1"), None);
+        // without the right
+        assert_eq!(castle("4k3/8/8/8/8/8/8/4K2R w - - 0 1", "e1", "h1"), None);
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use super::{board::Board, piece_move::ChessMove, position::Position};
 
 pub mod pawn;
@@ -16,8 +18,44 @@ pub enum PieceType {
     Queen,
     King
 }
+
+impl PieceType {
+    pub fn get_letter(&self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Rook => 'R',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter.to_ascii_uppercase() {
+            'P' => Some(PieceType::Pawn),
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'R' => Some(PieceType::Rook),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+
+    pub fn get_value(&self) -> i32 {
+        match self {
+            PieceType::Pawn => 1,
+            PieceType::Knight => 3,
+            PieceType::Bishop => 3,
+            PieceType::Rook => 5,
+            PieceType::Queen => 9,
+            PieceType::King => 0,
+        }
+    }
+}
 // #[default] White
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Player {
     White,
     Black
@@ -33,7 +71,7 @@ impl Player {
     }
 }
 
-pub trait ChessPiece: dyn_clone::DynClone {
+pub trait ChessPiece: dyn_clone::DynClone + Send + Sync {
     fn get_color(&self) -> Player;
 
     fn get_piece_type(&self) -> PieceType;
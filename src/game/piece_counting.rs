@@ -1,10 +1,90 @@
 use std::collections::HashMap;
 
-use super::piece::PieceType;
+use super::piece::{PieceType, Player};
 
 #[derive(Debug, Clone)]
 pub struct PieceCounting {
     pub total_pieces: i32,
     pub black_pieces: HashMap<PieceType, i8>,
     pub white_pieces: HashMap<PieceType, i8>
-}
\ No newline at end of file
+}
+
+impl PieceCounting {
+    pub fn get_pieces(&self, player: Player) -> &HashMap<PieceType, i8> {
+        if player == Player::White {
+            return &self.white_pieces;
+        }
+
+        &self.black_pieces
+    }
+
+    pub fn get_material(&self, player: Player) -> i32 {
+        self.get_pieces(player).iter()
+            .map(|(piece_type, count)| piece_type.get_value() * *count as i32)
+            .sum()
+    }
+
+    pub fn has_mating_material(&self, player: Player) -> bool {
+        let pieces = self.get_pieces(player);
+        let count = |piece_type| *pieces.get(&piece_type).unwrap_or(&0);
+
+        count(PieceType::Pawn) + count(PieceType::Rook) + count(PieceType::Queen) > 0
+            || count(PieceType::Bishop) + count(PieceType::Knight) >= 2
+    }
+
+    // the pieces the player took, counted against the pieces the game started with
+    pub fn get_captured_pieces(&self, start: &PieceCounting, player: Player) -> Vec<PieceType> {
+        let opponent_pieces = self.get_pieces(player.get_opponent());
+        let starting_pieces = start.get_pieces(player.get_opponent());
+        let mut captured_pieces = Vec::new();
+        let mut promotions = 0;
+
+        for piece_type in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight] {
+            let count = *opponent_pieces.get(&piece_type).unwrap_or(&0);
+            let starting_count = *starting_pieces.get(&piece_type).unwrap_or(&0);
+
+            promotions += (count - starting_count).max(0);
+
+            for _ in count..starting_count {
+                captured_pieces.push(piece_type);
+            }
+        }
+
+        let pawn_count = *opponent_pieces.get(&PieceType::Pawn).unwrap_or(&0);
+        let starting_pawn_count = *starting_pieces.get(&PieceType::Pawn).unwrap_or(&0);
+
+        for _ in (pawn_count + promotions)..starting_pawn_count {
+            captured_pieces.push(PieceType::Pawn);
+        }
+
+        captured_pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{fen, piece::{PieceType, Player}};
+
+    fn get_captured_pieces(start: &str, current: &str, player: Player) -> Vec<PieceType> {
+        let start = fen::parse_fen(start).unwrap().board.get_total_pieces();
+
+        fen::parse_fen(current).unwrap().board.get_total_pieces().get_captured_pieces(&start, player)
+    }
+
+    #[test]
+    fn counts_captures_from_the_start_position() {
+        let start = "8/8/4k3/3p4/8/8/4K3/R7 w - - 0 1";
+
+        assert!(get_captured_pieces(start, start, Player::White).is_empty());
+        assert!(get_captured_pieces(start, start, Player::Black).is_empty());
+        assert_eq!(get_captured_pieces(start, "8/8/4k3/8/8/8/4K3/R7 b - - 0 1", Player::White), vec![PieceType::Pawn]);
+        assert_eq!(get_captured_pieces(start, "8/8/4k3/3p4/8/8/4K3/8 w - - 0 1", Player::Black), vec![PieceType::Rook]);
+    }
+
+    #[test]
+    fn counts_promoted_pieces_against_the_missing_pawns() {
+        let start = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+
+        assert!(get_captured_pieces(start, "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1", Player::Black).is_empty());
+    }
+}
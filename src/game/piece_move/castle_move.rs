@@ -6,44 +6,41 @@ use super::ChessMove;
 pub struct CastleMove {
     pub from_pos: Position,
     pub to_pos: Position,
+    pub king_to: Position,
+    pub rook_from: Position,
+    pub rook_to: Position,
 }
 
 impl CastleMove {
-    pub fn new(from_pos: Position, to_pos: Position) -> Self {
+    pub fn new(from_pos: Position, rook_from: Position) -> Self {
+        let is_short = rook_from.column > from_pos.column;
+        let king_to = Position::new(if is_short { 6 } else { 2 }, from_pos.row);
+        let rook_to = Position::new(if is_short { 5 } else { 3 }, from_pos.row);
+
+        // a king that only shifts one square (or not at all) castles by moving onto its rook,
+        // so the move can't be confused with a normal king step
+        let to_pos = if (king_to.column - from_pos.column).abs() >= 2 { king_to } else { rook_from };
+
         Self {
             from_pos,
-            to_pos
+            to_pos,
+            king_to,
+            rook_from,
+            rook_to,
         }
     }
 }
 
 impl ChessMove for CastleMove {
     fn execute(&self, board: &mut Board) {
-        let mut piece = board[self.from_pos].clone().unwrap();
+        let mut piece = board[self.from_pos].take().unwrap();
+        let mut rook = board[self.rook_from].take().unwrap();
 
         piece.set_has_moved();
+        rook.set_has_moved();
 
-
-        if self.to_pos.column == 2 {
-            let mut rook = board.state[0][self.to_pos.row as usize].clone().unwrap();
-
-            rook.set_has_moved();
-
-            board.state[0][self.to_pos.row as usize] = None;
-            board.state[3][self.to_pos.row as usize] = Some(rook);
-        }
-
-        if self.to_pos.column == 6 {
-            let mut rook = board.state[7][self.to_pos.row as usize].clone().unwrap();
-
-            rook.set_has_moved();
-
-            board.state[7][self.to_pos.row as usize] = None;
-            board.state[5][self.to_pos.row as usize] = Some(rook);
-        }
-        
-        board[self.from_pos] = None;
-        board[self.to_pos] = Some(piece);
+        board[self.king_to] = Some(piece);
+        board[self.rook_to] = Some(rook);
 
         board.en_passant = None;
     }
@@ -59,4 +56,16 @@ impl ChessMove for CastleMove {
     fn is_pawn_promotion_move(&self) -> bool {
         return false
     }
+
+    fn is_castle_move(&self) -> bool {
+        true
+    }
+
+    fn get_castle_rook(&self) -> Option<(Position, Position)> {
+        Some((self.rook_from, self.rook_to))
+    }
+
+    fn get_king_to(&self) -> Position {
+        self.king_to
+    }
 }
\ No newline at end of file
@@ -6,11 +6,20 @@ pub mod en_passant_move;
 pub mod castle_move;
 pub mod pawn_promotion_move;
 
-pub trait ChessMove: dyn_clone::DynClone {
+pub trait ChessMove: dyn_clone::DynClone + Send + Sync {
     fn execute(&self, board: &mut Board);
     fn get_to_pos(&self) -> Position;
     fn get_from_pos(&self) -> Position;
     fn is_pawn_promotion_move(&self) -> bool;
+    fn is_castle_move(&self) -> bool {
+        false
+    }
+    fn get_castle_rook(&self) -> Option<(Position, Position)> {
+        None
+    }
+    fn get_king_to(&self) -> Position {
+        self.get_to_pos()
+    }
 }
 
 dyn_clone::clone_trait_object!(ChessMove);
\ No newline at end of file
@@ -0,0 +1,275 @@
+use std::{collections::VecDeque, time::{Duration, Instant}};
+
+use ggez::input::keyboard::KeyCode;
+
+use crate::engine::{EngineOptions, EnginePlayer, EngineRequest};
+
+use super::{board::Board, clock::Clock, game_config::PlayerKind, notation, piece::Player};
+
+const REPLAY_INTERVAL: Duration = Duration::from_millis(800);
+
+// the position a controller is asked to move in
+// borrowed from the game, it is made every frame and only engines need a copy
+pub struct MoveRequest<'a> {
+    pub board: &'a Board,
+    pub player: Player,
+    pub start_fen: &'a str,
+    pub moves: &'a [String],
+    pub fifty_move_rule: u8,
+    pub clock: Option<&'a Clock>,
+    pub ply: usize,
+}
+
+pub struct ControllerMove {
+    pub ply: usize,
+    pub uci: String,
+    // the clocks as the mover saw them, sent along with network moves
+    pub clock: Option<[f32; 2]>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ControllerKind {
+    Mouse,
+    Keyboard,
+    Engine,
+    Network,
+    Replay,
+}
+
+// decides the moves of one side, the game asks the controller of the player to move
+pub trait PlayerController {
+    fn get_kind(&self) -> ControllerKind;
+
+    fn is_human(&self) -> bool {
+        matches!(self.get_kind(), ControllerKind::Mouse | ControllerKind::Keyboard)
+    }
+
+    // a move decided outside the controller, on the board or by the peer
+    fn submit_move(&mut self, _controller_move: ControllerMove) {}
+
+    // returns true if the controller took the key press
+    fn handle_input(&mut self, _key_press: Option<KeyCode>, _text_input: &str) -> bool {
+        false
+    }
+
+    fn get_prompt(&self) -> Option<String> {
+        None
+    }
+
+    fn poll_move(&mut self, request: &MoveRequest) -> Option<Result<ControllerMove, String>>;
+}
+
+pub fn new_controller(kind: &PlayerKind, is_chess960: bool, options: &EngineOptions) -> Result<Box<dyn PlayerController>, String> {
+    Ok(match kind {
+        PlayerKind::Mouse => Box::new(MouseController::default()),
+        PlayerKind::Keyboard => Box::new(KeyboardController::default()),
+        PlayerKind::Engine(level) => Box::new(EngineController::new(EnginePlayer::built_in(*level).with_options(options.clone()))),
+        PlayerKind::External(path) => Box::new(EngineController::new(EnginePlayer::external(path, is_chess960, options.threads)?)),
+        PlayerKind::Network => Box::new(NetworkController::default()),
+        PlayerKind::Replay(moves) => Box::new(ReplayController::new(moves.clone(), REPLAY_INTERVAL)),
+    })
+}
+
+// plays the moves picked on the board
+#[derive(Default)]
+pub struct MouseController {
+    chosen_move: Option<ControllerMove>,
+}
+
+impl PlayerController for MouseController {
+    fn get_kind(&self) -> ControllerKind {
+        ControllerKind::Mouse
+    }
+
+    fn submit_move(&mut self, controller_move: ControllerMove) {
+        self.chosen_move = Some(controller_move);
+    }
+
+    fn poll_move(&mut self, request: &MoveRequest) -> Option<Result<ControllerMove, String>> {
+        self.chosen_move.take().filter(|chosen_move| chosen_move.ply == request.ply).map(Ok)
+    }
+}
+
+// plays moves typed in SAN or UCI and confirmed with enter, the board works as well
+#[derive(Default)]
+pub struct KeyboardController {
+    text: String,
+    entered: Option<String>,
+    error: Option<String>,
+    chosen_move: Option<ControllerMove>,
+}
+
+impl KeyboardController {
+    fn find_move(request: &MoveRequest, text: &str) -> Option<String> {
+        let is_legal_uci = notation::parse_uci(&text.to_lowercase())
+            .and_then(|(from_pos, to_pos, _)| request.board.find_move(request.player, from_pos, to_pos))
+            .is_some();
+
+        if is_legal_uci {
+            return Some(text.to_lowercase());
+        }
+
+        notation::parse_san(request.board, request.player, text)
+    }
+}
+
+impl PlayerController for KeyboardController {
+    fn get_kind(&self) -> ControllerKind {
+        ControllerKind::Keyboard
+    }
+
+    fn submit_move(&mut self, controller_move: ControllerMove) {
+        self.chosen_move = Some(controller_move);
+    }
+
+    fn handle_input(&mut self, key_press: Option<KeyCode>, text_input: &str) -> bool {
+        self.text.extend(text_input.chars().filter(|c| c.is_ascii_graphic()));
+
+        match key_press {
+            Some(KeyCode::Back) => {
+                self.text.pop();
+            },
+            Some(KeyCode::Return) if !self.text.is_empty() => self.entered = Some(std::mem::take(&mut self.text)),
+            _ => (),
+        }
+
+        !text_input.is_empty() || matches!(key_press, Some(KeyCode::Back | KeyCode::Return))
+    }
+
+    fn get_prompt(&self) -> Option<String> {
+        Some(match &self.error {
+            Some(error) if self.text.is_empty() => error.clone(),
+            _ => format!("Move: {}_", self.text),
+        })
+    }
+
+    fn poll_move(&mut self, request: &MoveRequest) -> Option<Result<ControllerMove, String>> {
+        if let Some(chosen_move) = self.chosen_move.take().filter(|chosen_move| chosen_move.ply == request.ply) {
+            self.error = None;
+
+            return Some(Ok(chosen_move));
+        }
+
+        let text = self.entered.take()?;
+
+        match Self::find_move(request, &text) {
+            Some(uci) => {
+                self.error = None;
+
+                Some(Ok(ControllerMove { ply: request.ply, uci, clock: None }))
+            },
+            None => {
+                self.error = Some(format!("'{}' is not a legal move", text));
+
+                None
+            },
+        }
+    }
+}
+
+pub struct EngineController {
+    engine: EnginePlayer,
+}
+
+impl EngineController {
+    pub fn new(engine: EnginePlayer) -> Self {
+        Self { engine }
+    }
+}
+
+impl PlayerController for EngineController {
+    fn get_kind(&self) -> ControllerKind {
+        ControllerKind::Engine
+    }
+
+    fn poll_move(&mut self, request: &MoveRequest) -> Option<Result<ControllerMove, String>> {
+        let result = if self.engine.is_thinking() {
+            self.engine.poll_move()
+        } else {
+            let engine_request = EngineRequest {
+                board: request.board.clone(),
+                player: request.player,
+                start_fen: request.start_fen.into(),
+                moves: request.moves.to_vec(),
+                fifty_move_rule: request.fifty_move_rule,
+                clock: request.clock.cloned(),
+            };
+
+            self.engine.request_move(engine_request).err().map(Err)
+        };
+
+        result.map(|result| result.map(|uci| ControllerMove { ply: request.ply, uci, clock: None }))
+    }
+}
+
+// plays the moves received from the other side of a network game, in order
+#[derive(Default)]
+pub struct NetworkController {
+    received_moves: VecDeque<ControllerMove>,
+}
+
+impl PlayerController for NetworkController {
+    fn get_kind(&self) -> ControllerKind {
+        ControllerKind::Network
+    }
+
+    fn submit_move(&mut self, controller_move: ControllerMove) {
+        self.received_moves.push_back(controller_move);
+    }
+
+    fn poll_move(&mut self, request: &MoveRequest) -> Option<Result<ControllerMove, String>> {
+        // moves sent again after a reconnection were already played
+        while self.received_moves.front().is_some_and(|received_move| received_move.ply < request.ply) {
+            self.received_moves.pop_front();
+        }
+
+        let received_move = self.received_moves.pop_front()?;
+
+        if received_move.ply > request.ply {
+            self.received_moves.clear();
+
+            return Some(Err(format!("move {} arrived before move {}", received_move.ply + 1, request.ply + 1)));
+        }
+
+        Some(Ok(received_move))
+    }
+}
+
+// plays the moves of a line given from the start of the game, one every interval
+pub struct ReplayController {
+    moves: Vec<String>,
+    interval: Duration,
+    waiting_since: Option<(usize, Instant)>,
+}
+
+impl ReplayController {
+    pub fn new(moves: Vec<String>, interval: Duration) -> Self {
+        Self {
+            moves,
+            interval,
+            waiting_since: None,
+        }
+    }
+}
+
+impl PlayerController for ReplayController {
+    fn get_kind(&self) -> ControllerKind {
+        ControllerKind::Replay
+    }
+
+    fn poll_move(&mut self, request: &MoveRequest) -> Option<Result<ControllerMove, String>> {
+        // the line is over, the side waits
+        let uci = self.moves.get(request.ply)?;
+
+        let since = match self.waiting_since {
+            Some((ply, since)) if ply == request.ply => since,
+            _ => self.waiting_since.insert((request.ply, Instant::now())).1,
+        };
+
+        if since.elapsed() < self.interval {
+            return None;
+        }
+
+        Some(Ok(ControllerMove { ply: request.ply, uci: uci.clone(), clock: None }))
+    }
+}
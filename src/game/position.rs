@@ -22,4 +22,23 @@ impl Position {
         
         Player::Black
     }
+
+    pub fn from_algebraic(square: &str) -> Option<Self> {
+        let mut chars = square.chars();
+        let file = chars.next()?;
+        let rank = chars.next()?.to_digit(10)? as i32;
+
+        if chars.next().is_some() || !('a'..='h').contains(&file) || !(1..=8).contains(&rank) {
+            return None;
+        }
+
+        Some(Self::new(file as i32 - 'a' as i32, 8 - rank))
+    }
+
+    pub fn to_algebraic(self) -> String {
+        let file = (b'a' + self.column as u8) as char;
+        let rank = 8 - self.row;
+
+        format!("{}{}", file, rank)
+    }
 }
\ No newline at end of file
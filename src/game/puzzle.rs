@@ -0,0 +1,213 @@
+use std::{fs::{self, File}, io::{BufRead, BufReader}, path::PathBuf};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::saved_game::SavedGame;
+
+const RATING_WINDOW: u32 = 150;
+const RATING_K_FACTOR: f32 = 32.;
+
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    pub id: String,
+    pub fen: String,
+    // the first move is the opponent's setup move, the rest alternate between solver and opponent
+    pub moves: Vec<String>,
+    pub rating: u32,
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    pub fn get_path() -> Option<PathBuf> {
+        SavedGame::get_data_dir().map(|dir| dir.join("puzzles.csv"))
+    }
+
+    // parses a row of the lichess puzzle database:
+    // PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+    pub fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+
+        if fields.len() < 8 {
+            return None;
+        }
+
+        let moves: Vec<String> = fields[2].split_whitespace().map(String::from).collect();
+
+        // the solution has to end on a move of the solver
+        if moves.len() < 2 || moves.len() % 2 == 1 {
+            return None;
+        }
+
+        Some(Self {
+            id: fields[0].into(),
+            fen: fields[1].into(),
+            moves,
+            rating: fields[3].parse().ok()?,
+            themes: fields[7].split_whitespace().map(String::from).collect(),
+        })
+    }
+
+    // picks a random puzzle close to the given rating, skipping the given ids
+    pub fn pick(rating: f32, skipped_ids: &[String]) -> Result<Self, String> {
+        let path = Self::get_path().ok_or(String::from("no data directory"))?;
+        let file = File::open(&path).map_err(|error| format!("could not open '{}': {}", path.display(), error))?;
+
+        Self::pick_from(BufReader::new(file), rating, skipped_ids, &mut rand::thread_rng())
+            .ok_or(format!("no puzzles found in '{}'", path.display()))
+    }
+
+    // every puzzle in the rating window is equally likely, wherever it is in the file
+    fn pick_from(reader: impl BufRead, rating: f32, skipped_ids: &[String], rng: &mut impl Rng) -> Option<Self> {
+        let target = rating.round().max(0.) as u32;
+        let mut candidate = None;
+        let mut candidate_count = 0;
+        let mut closest: Option<Puzzle> = None;
+
+        for line in reader.lines().map_while(Result::ok) {
+            let Some(puzzle) = Self::parse(&line) else {
+                continue;
+            };
+
+            if skipped_ids.contains(&puzzle.id) {
+                continue;
+            }
+
+            if puzzle.rating.abs_diff(target) <= RATING_WINDOW {
+                candidate_count += 1;
+
+                if rng.gen_range(0..candidate_count) == 0 {
+                    candidate = Some(puzzle);
+                }
+            }
+            else if closest.as_ref().is_none_or(|closest| puzzle.rating.abs_diff(target) < closest.rating.abs_diff(target)) {
+                closest = Some(puzzle);
+            }
+        }
+
+        candidate.or(closest)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct PuzzleStats {
+    pub rating: f32,
+    pub streak: u32,
+    pub best_streak: u32,
+    pub solved: u32,
+    pub failed: u32,
+}
+
+impl Default for PuzzleStats {
+    fn default() -> Self {
+        Self {
+            rating: 1500.,
+            streak: 0,
+            best_streak: 0,
+            solved: 0,
+            failed: 0,
+        }
+    }
+}
+
+impl PuzzleStats {
+    pub fn get_path() -> Option<PathBuf> {
+        SavedGame::get_data_dir().map(|dir| dir.join("puzzles.toml"))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::get_path() else {
+            return Self::default();
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::default(),
+        };
+
+        toml::from_str(&contents).unwrap_or_else(|error| {
+            println!("Invalid puzzle stats file '{}': {}", path.display(), error);
+
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::get_path() else {
+            return;
+        };
+
+        let result = toml::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+
+                fs::write(&path, contents).map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            println!("Could not save puzzle stats to '{}': {}", path.display(), error);
+        }
+    }
+
+    // updates the rating like an elo game against the puzzle, returns the rating change
+    pub fn record(&mut self, puzzle_rating: u32, is_solved: bool) -> f32 {
+        let expected = 1. / (1. + 10f32.powf((puzzle_rating as f32 - self.rating) / 400.));
+        let change = RATING_K_FACTOR * (if is_solved { 1. } else { 0. } - expected);
+
+        self.rating += change;
+
+        if is_solved {
+            self.solved += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        }
+        else {
+            self.failed += 1;
+            self.streak = 0;
+        }
+
+        self.save();
+
+        change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn get_rows(ratings: &[u32]) -> String {
+        ratings.iter().enumerate()
+            .map(|(i, rating)| format!("p{},4k3/8/8/8/8/8/4P3/4K3 w - - 0 1,e2e4 e8d7,{},75,90,100,mate,,\n", i, rating))
+            .collect()
+    }
+
+    #[test]
+    fn picks_from_the_whole_file() {
+        let rows = get_rows(&[1500; 1000]);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let picks: Vec<usize> = (0..200)
+            .map(|_| Puzzle::pick_from(rows.as_bytes(), 1500., &[], &mut rng).unwrap().id[1..].parse().unwrap())
+            .collect();
+
+        assert!(picks.iter().any(|index| *index >= 500));
+        assert!(picks.iter().any(|index| *index < 500));
+    }
+
+    #[test]
+    fn picks_the_closest_puzzle_outside_the_window() {
+        let rows = get_rows(&[800, 2400, 1100]);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(Puzzle::pick_from(rows.as_bytes(), 1500., &[], &mut rng).unwrap().id, "p2");
+        assert_eq!(Puzzle::pick_from(rows.as_bytes(), 1500., &["p2".into()], &mut rng).unwrap().id, "p0");
+        assert!(Puzzle::pick_from("".as_bytes(), 1500., &[], &mut rng).is_none());
+    }
+}
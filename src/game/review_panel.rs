@@ -0,0 +1,117 @@
+use ggez::{glam::Vec2, graphics::{self, Canvas, Color, DrawParam, PxScale, Rect, Text, TextFragment}, Context};
+
+use crate::{engine::review::GameReview, widgets::Button};
+
+use super::{board::Board, game_event::GameEvent, history::HistoryEntry, notation, piece::Player, position::Position};
+
+const PANEL_X: f32 = 292.;
+const PANEL_Y: f32 = 130.;
+
+pub struct ReviewPanel {
+    previous_button: Button,
+    done_button: Button,
+    next_button: Button,
+}
+
+impl ReviewPanel {
+    pub fn new(ctx: &mut Context) -> Self {
+        let mut buttons = [("< Mistake", 0., 58.), ("Done", 60., 34.), ("Mistake >", 96., 58.)].into_iter().map(|(label, x, width)| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., width, 20.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: label.into(),
+                    scale: Some(PxScale::from(12.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [PANEL_X + x, 214.].into(),
+                ctx
+            )
+        }).collect::<Vec<Button>>().into_iter();
+
+        Self {
+            previous_button: buttons.next().unwrap(),
+            done_button: buttons.next().unwrap(),
+            next_button: buttons.next().unwrap(),
+        }
+    }
+
+    // the better move for the move that led to `ply`, if the played one was not the best
+    pub fn get_better_move(review: &GameReview, ply: usize) -> Option<(Position, Position)> {
+        let reviewed_move = review.get_reviewed_move(ply)?;
+
+        if !reviewed_move.class.is_mistake() {
+            return None;
+        }
+
+        notation::parse_uci(reviewed_move.best_move.as_ref()?).map(|(from_pos, to_pos, _)| (from_pos, to_pos))
+    }
+
+    // position_before is the position the move leading to `ply` was played from
+    pub fn draw(&self, review: &GameReview, history: &[HistoryEntry], position_before: (&Board, Player), ply: usize, canvas: &mut Canvas, ctx: &mut Context) {
+        let (done, total) = review.get_progress();
+
+        let summary = if review.is_finished() {
+            let format_accuracy = |player| review.get_accuracy(player).map(|accuracy| format!("{:.0}%", accuracy)).unwrap_or("-".into());
+
+            format!("Accuracy W {} B {}", format_accuracy(Player::White), format_accuracy(Player::Black))
+        } else {
+            format!("Reviewing {}/{}", done, total)
+        };
+
+        let mut lines = vec![summary];
+
+        if let Some(reviewed_move) = review.get_reviewed_move(ply) {
+            let entry = &history[ply - 1];
+
+            lines.push(format!("{}{} {:?}", entry.san, reviewed_move.class.get_symbol(), reviewed_move.class));
+
+            if reviewed_move.class.is_mistake() {
+                let (board_before, player) = position_before;
+
+                if let Some(best_move) = reviewed_move.best_move {
+                    if let Some(san) = notation::get_san_line(board_before, player, &[best_move]).first() {
+                        lines.push(format!("Best was {}", san));
+                    }
+                }
+            }
+        }
+
+        lines.push(format!("{} mistakes", review.get_mistakes().len()));
+
+        for (i, line) in lines.iter().enumerate() {
+            let mut line_text = Text::new(line.as_str());
+            line_text.set_scale(12.);
+            line_text.set_bounds([152., 14.]);
+
+            canvas.draw(&line_text, DrawParam::new().dest([PANEL_X, PANEL_Y + 16. * i as f32]).color(Color::BLACK));
+        }
+
+        self.previous_button.draw(canvas, ctx);
+        self.done_button.draw(canvas, ctx);
+        self.next_button.draw(canvas, ctx);
+    }
+
+    pub fn check_for_message(&self, press_position: Option<Vec2>, review: &GameReview, ply: usize) -> Option<GameEvent> {
+        if self.done_button.is_clicked(press_position) {
+            return Some(GameEvent::CloseReview);
+        }
+
+        let mistakes = review.get_mistakes();
+
+        let target = if self.previous_button.is_clicked(press_position) {
+            mistakes.into_iter().rev().find(|mistake| *mistake < ply)
+        } else if self.next_button.is_clicked(press_position) {
+            mistakes.into_iter().find(|mistake| *mistake > ply)
+        } else {
+            None
+        };
+
+        target.map(|mistake| GameEvent::ShowMove(mistake - 1))
+    }
+}
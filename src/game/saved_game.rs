@@ -0,0 +1,119 @@
+use std::{cmp::Reverse, fs, path::{Path, PathBuf}, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use super::game_config::GameConfig;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedGame {
+    pub moves: Vec<String>,
+    #[serde(default)]
+    pub config: GameConfig,
+    pub clock: Option<[f32; 2]>,
+    pub flipped: bool,
+}
+
+impl SavedGame {
+    pub fn get_data_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("rust_games"))
+    }
+
+    pub fn get_saves_dir() -> Option<PathBuf> {
+        Self::get_data_dir().map(|dir| dir.join("saves"))
+    }
+
+    pub fn get_autosave_path() -> Option<PathBuf> {
+        Self::get_data_dir().map(|dir| dir.join("autosave.toml"))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+        toml::from_str(&contents).map_err(|error| error.to_string())
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let contents = toml::to_string(self).map_err(|error| error.to_string())?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        }
+
+        fs::write(path, contents).map_err(|error| error.to_string())
+    }
+
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let saves_dir = Self::get_saves_dir().ok_or(String::from("no data directory"))?;
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or(0);
+
+        // saves in the same millisecond get a number instead of overwriting each other
+        let mut path = saves_dir.join(format!("game-{}.toml", timestamp));
+        let mut count = 1;
+
+        while path.exists() {
+            count += 1;
+            path = saves_dir.join(format!("game-{}-{}.toml", timestamp, count));
+        }
+
+        self.write(&path)?;
+
+        Ok(path)
+    }
+
+    pub fn list_saves() -> Vec<PathBuf> {
+        let Some(saves_dir) = Self::get_saves_dir() else {
+            return Vec::new();
+        };
+
+        let mut saves: Vec<(SystemTime, PathBuf)> = fs::read_dir(saves_dir)
+            .map(|entries| entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+                .map(|path| (fs::metadata(&path).and_then(|metadata| metadata.modified()).unwrap_or(SystemTime::UNIX_EPOCH), path))
+                .collect())
+            .unwrap_or_default();
+
+        saves.sort_by_key(|(modified, _)| Reverse(*modified));
+
+        saves.into_iter().map(|(_, path)| path).collect()
+    }
+
+    pub fn read_autosave() -> Option<Self> {
+        let path = Self::get_autosave_path()?;
+
+        if !path.exists() {
+            return None;
+        }
+
+        match Self::read(&path) {
+            Ok(saved_game) => Some(saved_game),
+            Err(error) => {
+                println!("Could not read autosave '{}': {}", path.display(), error);
+
+                None
+            }
+        }
+    }
+
+    pub fn write_autosave(&self) {
+        if let Some(path) = Self::get_autosave_path() {
+            if let Err(error) = self.write(&path) {
+                println!("Could not write autosave '{}': {}", path.display(), error);
+            }
+        }
+    }
+
+    pub fn delete_autosave() {
+        if let Some(path) = Self::get_autosave_path() {
+            if path.exists() {
+                if let Err(error) = fs::remove_file(&path) {
+                    println!("Could not delete autosave '{}': {}", path.display(), error);
+                }
+            }
+        }
+    }
+}
@@ -6,19 +6,59 @@ use ggez::{
 };
 
 mod assets;
+mod sounds;
+mod settings;
 mod game;
+mod engine;
+mod network;
 mod app;
 mod widgets;
 mod scenes;
+mod server;
 
 
 
 pub fn main() -> GameResult {
+    let args: Vec<String> = std::env::args().collect();
+
+    // runs headless, e.g. `rust_games --server 127.0.0.1:8000`
+    if let Some(index) = args.iter().position(|arg| arg == "--server") {
+        let address = match args.get(index + 1) {
+            Some(port) if port.parse::<u16>().is_ok() => format!("127.0.0.1:{}", port),
+            Some(address) => address.clone(),
+            None => server::DEFAULT_ADDRESS.into(),
+        };
+
+        if let Err(error) = server::run(&address) {
+            println!("{}", error);
+        }
+
+        return Ok(());
+    }
+
+    // plays engines against each other headless, e.g. `rust_games --match builtin:2 builtin:4 --games 20`
+    if let Some(index) = args.iter().position(|arg| arg == "--match") {
+        if let Err(error) = engine::match_runner::run(&args[index + 1..]) {
+            println!("{}", error);
+        }
+
+        return Ok(());
+    }
+
+    // searches fixed positions to a fixed depth and prints the node count, e.g. `rust_games --bench 6`
+    if let Some(index) = args.iter().position(|arg| arg == "--bench") {
+        if let Err(error) = engine::bench::run(&args[index + 1..]) {
+            println!("{}", error);
+        }
+
+        return Ok(());
+    }
+
     let cb = ggez::ContextBuilder::new("super_simple", "ggez")
         .add_resource_path("./resources")
         .window_mode(
             ggez::conf::WindowMode::default()
-                .dimensions(288., 288.)
+                .dimensions(448., 288.)
         )
         .window_setup(
             ggez::conf::WindowSetup::default()
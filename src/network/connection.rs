@@ -0,0 +1,265 @@
+use std::{io::{self, BufRead, BufReader, Write}, net::{IpAddr, Shutdown, TcpListener, TcpStream, ToSocketAddrs, UdpSocket}, sync::mpsc::{self, Receiver, Sender}, thread, time::{Duration, Instant}};
+
+use super::protocol::NetMessage;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+const PEER_TIMEOUT: Duration = Duration::from_secs(8);
+
+enum PeerEvent {
+    Connected(TcpStream),
+    ConnectFailed(String),
+    Received(NetMessage),
+    Closed(String),
+}
+
+pub enum ConnectionEvent {
+    Connected,
+    Received(NetMessage),
+    Disconnected(String),
+}
+
+// the address other machines on the network most likely reach us at
+pub fn get_lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+
+    // nothing is sent, this only picks the outgoing interface
+    socket.connect("8.8.8.8:80").ok()?;
+
+    socket.local_addr().ok().map(|address| address.ip())
+}
+
+// a single tcp connection to the other player that keeps itself alive:
+// the host accepts a new connection and the joining side reconnects when it drops
+pub struct NetPeer {
+    listener: Option<TcpListener>,
+    address: Option<String>,
+    stream: Option<TcpStream>,
+    sender: Sender<(u64, PeerEvent)>,
+    receiver: Receiver<(u64, PeerEvent)>,
+    // identifies the current stream, events of older ones are ignored
+    generation: u64,
+    is_connecting: bool,
+    last_attempt: Option<Instant>,
+    last_received: Instant,
+    last_sent: Instant,
+    pub last_error: Option<String>,
+}
+
+impl NetPeer {
+    fn new(listener: Option<TcpListener>, address: Option<String>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            listener,
+            address,
+            stream: None,
+            sender,
+            receiver,
+            generation: 0,
+            is_connecting: false,
+            last_attempt: None,
+            last_received: Instant::now(),
+            last_sent: Instant::now(),
+            last_error: None,
+        }
+    }
+
+    pub fn listen(port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|error| format!("could not listen on port {}: {}", port, error))?;
+
+        listener.set_nonblocking(true).map_err(|error| error.to_string())?;
+
+        Ok(Self::new(Some(listener), None))
+    }
+
+    pub fn connect(address: &str) -> Self {
+        Self::new(None, Some(address.into()))
+    }
+
+    pub fn get_port(&self) -> Option<u16> {
+        self.listener.as_ref()?.local_addr().ok().map(|address| address.port())
+    }
+
+    // stops reconnecting, for when the other side refused us
+    pub fn stop(&mut self) {
+        self.address = None;
+        self.close();
+    }
+
+    pub fn close(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+
+        self.generation += 1;
+    }
+
+    pub fn send(&mut self, message: &NetMessage) -> Result<(), String> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Err("not connected".into());
+        };
+
+        let result = writeln!(stream, "{}", message.encode()).and_then(|_| stream.flush());
+
+        if let Err(error) = result {
+            // the reader thread reports the disconnect
+            let _ = stream.shutdown(Shutdown::Both);
+
+            return Err(error.to_string());
+        }
+
+        self.last_sent = Instant::now();
+
+        Ok(())
+    }
+
+    fn attach(&mut self, stream: TcpStream, events: &mut Vec<ConnectionEvent>) {
+        let reader = match stream.set_nonblocking(false).and_then(|_| stream.set_nodelay(true)).and_then(|_| stream.try_clone()) {
+            Ok(reader) => reader,
+            Err(error) => {
+                self.last_error = Some(error.to_string());
+
+                return;
+            },
+        };
+
+        self.close();
+
+        let generation = self.generation;
+        let sender = self.sender.clone();
+
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(error) => {
+                        let _ = sender.send((generation, PeerEvent::Closed(error.to_string())));
+
+                        return;
+                    },
+                };
+
+                match NetMessage::decode(&line) {
+                    Ok(message) => {
+                        if sender.send((generation, PeerEvent::Received(message))).is_err() {
+                            return;
+                        }
+                    },
+                    Err(error) => println!("Ignoring invalid message '{}': {}", line, error),
+                }
+            }
+
+            let _ = sender.send((generation, PeerEvent::Closed("connection closed".into())));
+        });
+
+        self.stream = Some(stream);
+        self.last_received = Instant::now();
+        self.last_sent = Instant::now();
+        self.last_error = None;
+
+        events.push(ConnectionEvent::Connected);
+    }
+
+    fn start_connecting(&mut self, address: String) {
+        self.is_connecting = true;
+        self.last_attempt = Some(Instant::now());
+
+        let sender = self.sender.clone();
+        let generation = self.generation;
+
+        thread::spawn(move || {
+            let result = address.to_socket_addrs()
+                .map_err(|error| error.to_string())
+                .and_then(|mut addresses| addresses.next().ok_or(format!("could not resolve '{}'", address)))
+                .and_then(|socket_address| TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT).map_err(|error| error.to_string()));
+
+            let _ = sender.send((generation, match result {
+                Ok(stream) => PeerEvent::Connected(stream),
+                Err(error) => PeerEvent::ConnectFailed(error),
+            }));
+        });
+    }
+
+    // rejects an extra connection, reading until it closes so the rejection is not lost to a reset
+    fn turn_away(mut stream: TcpStream) {
+        let _ = stream.set_nonblocking(false);
+        let _ = writeln!(stream, "{}", NetMessage::Reject { reason: "the game is full".into() }.encode());
+        let _ = stream.shutdown(Shutdown::Write);
+        let _ = stream.set_read_timeout(Some(PEER_TIMEOUT));
+        let _ = io::copy(&mut stream, &mut io::sink());
+    }
+
+    pub fn poll(&mut self) -> Vec<ConnectionEvent> {
+        let mut events = Vec::new();
+
+        if let Some(listener) = &self.listener {
+            match listener.accept() {
+                Ok((stream, _)) if self.stream.is_none() => self.attach(stream, &mut events),
+                Ok((stream, _)) => {
+                    thread::spawn(move || Self::turn_away(stream));
+                },
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => (),
+                Err(error) => self.last_error = Some(error.to_string()),
+            }
+        }
+        else if let Some(address) = self.address.clone() {
+            let is_due = self.last_attempt.is_none_or(|last_attempt| last_attempt.elapsed() >= RECONNECT_INTERVAL);
+
+            if self.stream.is_none() && !self.is_connecting && is_due {
+                self.start_connecting(address);
+            }
+        }
+
+        while let Ok((generation, event)) = self.receiver.try_recv() {
+            match event {
+                PeerEvent::Connected(stream) => {
+                    self.is_connecting = false;
+
+                    if self.address.is_some() && self.stream.is_none() {
+                        self.attach(stream, &mut events);
+                    }
+                },
+                PeerEvent::ConnectFailed(error) => {
+                    self.is_connecting = false;
+                    self.last_error = Some(error);
+                },
+                _ if generation != self.generation => (),
+                PeerEvent::Received(message) => {
+                    self.last_received = Instant::now();
+
+                    if !matches!(message, NetMessage::Ping) {
+                        events.push(ConnectionEvent::Received(message));
+                    }
+                },
+                PeerEvent::Closed(reason) => {
+                    self.close();
+                    self.last_error = Some(reason.clone());
+
+                    events.push(ConnectionEvent::Disconnected(reason));
+                },
+            }
+        }
+
+        if self.stream.is_some() {
+            if self.last_received.elapsed() > PEER_TIMEOUT {
+                self.close();
+                self.last_error = Some("timed out".into());
+
+                events.push(ConnectionEvent::Disconnected("timed out".into()));
+            }
+            else if self.last_sent.elapsed() > PING_INTERVAL {
+                let _ = self.send(&NetMessage::Ping);
+            }
+        }
+
+        events
+    }
+}
+
+impl Drop for NetPeer {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
@@ -0,0 +1,320 @@
+use std::time::{Duration, Instant};
+
+use connection::{ConnectionEvent, NetPeer};
+use protocol::{NetMessage, DEFAULT_PORT, PROTOCOL_VERSION};
+
+use crate::game::saved_game::SavedGame;
+
+pub mod connection;
+pub mod protocol;
+
+const MAX_CHAT_LINES: usize = 50;
+const CLOCK_SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DrawOffer {
+    None,
+    Sent,
+    Received,
+}
+
+// a game against another instance of the app, hosted by one side and joined by the other
+pub struct NetSession {
+    peer: NetPeer,
+    pub is_host: bool,
+    pub session: Option<u64>,
+    // both sides completed the handshake on the current connection
+    pub is_ready: bool,
+    pub has_joined: bool,
+    pub status: String,
+    pub chat: Vec<String>,
+    pub draw_offer: DrawOffer,
+    last_clock_sync: Instant,
+}
+
+impl NetSession {
+    fn new(peer: NetPeer, is_host: bool, session: Option<u64>, status: String) -> Self {
+        Self {
+            peer,
+            is_host,
+            session,
+            is_ready: false,
+            has_joined: false,
+            status,
+            chat: Vec::new(),
+            draw_offer: DrawOffer::None,
+            last_clock_sync: Instant::now(),
+        }
+    }
+
+    pub fn host(port: u16) -> Result<Self, String> {
+        let peer = NetPeer::listen(port)?;
+        let port = peer.get_port().unwrap_or(port);
+
+        let status = match connection::get_lan_address() {
+            Some(address) => format!("Waiting on {}:{}", address, port),
+            None => format!("Waiting on port {}", port),
+        };
+
+        Ok(Self::new(peer, true, Some(rand::random()), status))
+    }
+
+    pub fn join(address: &str) -> Self {
+        let address = if address.contains(':') { address.to_string() } else { format!("{}:{}", address, DEFAULT_PORT) };
+        let status = format!("Connecting to {}", address);
+
+        Self::new(NetPeer::connect(&address), false, None, status)
+    }
+
+    pub fn send(&mut self, message: NetMessage) {
+        if let Err(error) = self.peer.send(&message) {
+            println!("Could not send {:?}: {}", message, error);
+        }
+    }
+
+    pub fn welcome(&mut self, game: SavedGame) {
+        let Some(session) = self.session else {
+            return;
+        };
+
+        self.send(NetMessage::Welcome { session, game });
+
+        self.is_ready = true;
+        self.has_joined = true;
+        self.status = "Opponent connected".into();
+    }
+
+    // the host's clock is the reference for both sides
+    pub fn sync_clock(&mut self, remaining: [f32; 2]) {
+        if !self.is_host || !self.is_ready || self.last_clock_sync.elapsed() < CLOCK_SYNC_INTERVAL {
+            return;
+        }
+
+        self.last_clock_sync = Instant::now();
+        self.send(NetMessage::ClockSync { remaining });
+    }
+
+    pub fn send_chat(&mut self, text: String) {
+        self.add_chat_line(format!("You: {}", text));
+        self.send(NetMessage::Chat { text });
+    }
+
+    fn add_chat_line(&mut self, line: String) {
+        self.chat.push(line);
+
+        if self.chat.len() > MAX_CHAT_LINES {
+            self.chat.remove(0);
+        }
+    }
+
+    fn reject(&mut self, reason: String) {
+        self.send(NetMessage::Reject { reason });
+        self.peer.close();
+    }
+
+    // handles the connection and handshake, returns the messages the game has to act on
+    pub fn poll(&mut self) -> Vec<NetMessage> {
+        let mut messages = Vec::new();
+
+        for event in self.peer.poll() {
+            match event {
+                ConnectionEvent::Connected if self.is_host => self.status = "Opponent connecting".into(),
+                ConnectionEvent::Connected => {
+                    self.status = "Connected, waiting for the host".into();
+                    self.send(NetMessage::Hello { version: PROTOCOL_VERSION, session: self.session });
+                },
+                ConnectionEvent::Disconnected(reason) => {
+                    self.is_ready = false;
+                    self.status = if self.is_host {
+                        format!("Opponent left ({}), waiting", reason)
+                    } else {
+                        format!("Connection lost ({}), reconnecting", reason)
+                    };
+                },
+                ConnectionEvent::Received(message) => match message {
+                    NetMessage::Hello { .. } if !self.is_host => (),
+                    NetMessage::Hello { version, .. } if version != PROTOCOL_VERSION => {
+                        self.reject(format!("protocol version {} is not supported", version));
+                    },
+                    NetMessage::Hello { session, .. } if self.has_joined && session != self.session => {
+                        self.reject("the game already has an opponent".into());
+                    },
+                    NetMessage::Welcome { session, .. } if !self.is_host => {
+                        self.session = Some(session);
+                        self.is_ready = true;
+                        self.has_joined = true;
+                        self.status = "Connected".into();
+
+                        messages.push(message);
+                    },
+                    NetMessage::Reject { reason } => {
+                        self.status = format!("Rejected: {}", reason);
+                        self.peer.stop();
+                        self.peer.last_error = None;
+
+                        // the disconnect that follows is expected
+                        return messages;
+                    },
+                    NetMessage::Chat { text } => self.add_chat_line(format!("Opponent: {}", text)),
+                    NetMessage::Error { message } => self.status = format!("Opponent: {}", message),
+                    _ if !self.is_ready && !matches!(message, NetMessage::Hello { .. }) => (),
+                    NetMessage::OfferDraw => {
+                        self.draw_offer = DrawOffer::Received;
+
+                        messages.push(message);
+                    },
+                    NetMessage::AnswerDraw { .. } => {
+                        self.draw_offer = DrawOffer::None;
+
+                        messages.push(message);
+                    },
+                    message => messages.push(message),
+                },
+            }
+        }
+
+        if !self.is_host && !self.has_joined {
+            if let Some(error) = &self.peer.last_error {
+                self.status = format!("Could not connect ({}), retrying", error);
+            }
+        }
+
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::{Duration, Instant}};
+
+    use crate::game::{game_config::GameConfig, saved_game::SavedGame};
+
+    use super::*;
+
+    const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn get_saved_game(moves: &[&str]) -> SavedGame {
+        SavedGame {
+            moves: moves.iter().map(|uci| uci.to_string()).collect(),
+            config: GameConfig::default(),
+            clock: None,
+            flipped: false,
+        }
+    }
+
+    // polls both sides until `receiver` gets a message `is_expected` accepts, the host welcomes every hello
+    fn wait_for(host: &mut NetSession, guest: &mut NetSession, to_guest: bool, is_expected: impl Fn(&NetMessage) -> bool) -> NetMessage {
+        let start = Instant::now();
+
+        while start.elapsed() < TEST_TIMEOUT {
+            for message in host.poll() {
+                if let NetMessage::Hello { .. } = message {
+                    host.welcome(get_saved_game(&["e2e4"]));
+                }
+
+                if !to_guest && is_expected(&message) {
+                    return message;
+                }
+            }
+
+            for message in guest.poll() {
+                if to_guest && is_expected(&message) {
+                    return message;
+                }
+            }
+
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        panic!("no message arrived, host: '{}', guest: '{}'", host.status, guest.status);
+    }
+
+    fn connect() -> (NetSession, NetSession) {
+        let mut host = NetSession::host(0).unwrap();
+        let mut guest = NetSession::join(&format!("127.0.0.1:{}", host.peer.get_port().unwrap()));
+
+        let welcome = wait_for(&mut host, &mut guest, true, |message| matches!(message, NetMessage::Welcome { .. }));
+
+        let NetMessage::Welcome { session, game } = welcome else {
+            unreachable!();
+        };
+
+        assert_eq!(Some(session), host.session);
+        assert_eq!(guest.session, host.session);
+        assert_eq!(game.moves, vec!["e2e4"]);
+        assert!(host.is_ready && guest.is_ready);
+
+        (host, guest)
+    }
+
+    #[test]
+    fn plays_moves_draw_offers_and_resignations() {
+        let (mut host, mut guest) = connect();
+
+        guest.send(NetMessage::Move { ply: 1, uci: "e7e5".into(), clock: None });
+
+        let message = wait_for(&mut host, &mut guest, false, |message| matches!(message, NetMessage::Move { .. }));
+        assert!(matches!(message, NetMessage::Move { ply: 1, uci, clock: None } if uci == "e7e5"));
+
+        host.draw_offer = DrawOffer::Sent;
+        host.send(NetMessage::OfferDraw);
+
+        wait_for(&mut host, &mut guest, true, |message| matches!(message, NetMessage::OfferDraw));
+        assert_eq!(guest.draw_offer, DrawOffer::Received);
+
+        guest.send(NetMessage::AnswerDraw { accepted: false });
+
+        wait_for(&mut host, &mut guest, false, |message| matches!(message, NetMessage::AnswerDraw { accepted: false }));
+        assert_eq!(host.draw_offer, DrawOffer::None);
+
+        guest.send(NetMessage::Resign);
+
+        wait_for(&mut host, &mut guest, false, |message| matches!(message, NetMessage::Resign));
+    }
+
+    #[test]
+    fn rejects_a_third_player() {
+        let (mut host, mut guest) = connect();
+        let mut third = NetSession::join(&format!("127.0.0.1:{}", host.peer.get_port().unwrap()));
+
+        let start = Instant::now();
+
+        while !third.status.starts_with("Rejected") {
+            assert!(start.elapsed() < TEST_TIMEOUT, "third player status: '{}'", third.status);
+
+            host.poll();
+            guest.poll();
+            third.poll();
+
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(third.status, "Rejected: the game is full");
+        assert!(guest.is_ready && host.is_ready);
+    }
+
+    #[test]
+    fn resyncs_after_reconnecting() {
+        let (mut host, mut guest) = connect();
+        let session = guest.session;
+
+        guest.peer.close();
+
+        // the host has to notice the old connection is gone before it accepts the new one
+        let start = Instant::now();
+
+        while host.is_ready {
+            assert!(start.elapsed() < TEST_TIMEOUT, "the host did not notice the disconnect");
+
+            host.poll();
+
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let welcome = wait_for(&mut host, &mut guest, true, |message| matches!(message, NetMessage::Welcome { .. }));
+
+        assert!(matches!(welcome, NetMessage::Welcome { game, .. } if game.moves == vec!["e2e4"]));
+        assert_eq!(guest.session, session);
+        assert!(guest.is_ready && host.is_ready);
+    }
+}
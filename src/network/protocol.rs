@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::saved_game::SavedGame;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7878;
+
+// every message is sent as one line of json
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetMessage {
+    // sent by the joining side, with the session it belonged to when reconnecting
+    Hello { version: u32, session: Option<u64> },
+    // the host's answer, with the game as seen by the joining side
+    Welcome { session: u64, game: SavedGame },
+    Reject { reason: String },
+    Move { ply: usize, uci: String, clock: Option<[f32; 2]> },
+    ClockSync { remaining: [f32; 2] },
+    OfferDraw,
+    AnswerDraw { accepted: bool },
+    Resign,
+    Chat { text: String },
+    Error { message: String },
+    Ping,
+}
+
+impl NetMessage {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn decode(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|error| error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_one_tagged_json_line() {
+        let line = NetMessage::Move { ply: 3, uci: "g1f3".into(), clock: Some([60., 55.5]) }.encode();
+
+        assert!(!line.contains('\n'));
+        assert!(line.contains("\"type\":\"move\""));
+        assert!(matches!(NetMessage::decode(&line), Ok(NetMessage::Move { ply: 3, uci, clock: Some([60., 55.5]) }) if uci == "g1f3"));
+        assert!(NetMessage::decode("{\"type\":\"teleport\"}").is_err());
+    }
+}
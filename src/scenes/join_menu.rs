@@ -0,0 +1,84 @@
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+
+use crate::{app::AppEvent, assets::Assets, game::game_config::Opponent, network::{protocol::NetMessage, NetSession}, widgets::Button};
+
+use super::Scene;
+
+// connects to a host and waits for its game before starting it
+pub struct JoinMenu {
+    pub address: String,
+    pub network: Option<NetSession>,
+    pub back_button: Button,
+}
+
+impl JoinMenu {
+    pub fn new(ctx: &mut Context, address: String) -> Self {
+        Self {
+            network: Some(NetSession::join(&address)),
+            address,
+            back_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 30.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Back".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 226.].into(),
+                ctx
+            ),
+        }
+    }
+}
+
+impl Scene for JoinMenu {
+    fn draw(&self, _assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let mut text = Text::new("Join Game");
+
+        text.set_scale(32.);
+        let x = 448./2. - text.dimensions(ctx).unwrap().w / 2.;
+
+        canvas.draw(&text, DrawParam::new().color(Color::BLACK).dest([x, 0.]));
+
+        if let Some(network) = &self.network {
+            let mut status_text = Text::new(network.status.as_str());
+            status_text.set_scale(16.);
+            status_text.set_bounds([440., 60.]);
+
+            let x = 448./2. - status_text.dimensions(ctx).unwrap().w / 2.;
+
+            canvas.draw(&status_text, DrawParam::new().color(Color::BLACK).dest([x, 120.]));
+        }
+
+        self.back_button.draw(canvas, ctx);
+    }
+
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _release_data: Option<(Vec2, MouseButton)>, _key_press: Option<KeyCode>, _text_input: &str, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        if self.back_button.is_clicked(press_data.map(|(position, _)| position)) {
+            app_events.push(AppEvent::OpenNewGame);
+
+            return;
+        }
+
+        let Some(network) = self.network.as_mut() else {
+            return;
+        };
+
+        for message in network.poll() {
+            if let NetMessage::Welcome { mut game, .. } = message {
+                game.config.opponent = Opponent::LanJoin(self.address.clone());
+
+                if let Some(network) = self.network.take() {
+                    app_events.push(AppEvent::StartNetworkGame(game, Box::new(network)));
+                }
+
+                return;
+            }
+        }
+    }
+}
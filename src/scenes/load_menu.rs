@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+
+use crate::{app::AppEvent, assets::Assets, game::saved_game::SavedGame, widgets::Button};
+
+use super::Scene;
+
+const VISIBLE_SAVES: usize = 6;
+
+pub struct LoadMenu {
+    pub saves: Vec<PathBuf>,
+    pub first_save: usize,
+    pub save_buttons: Vec<Button>,
+    pub previous_button: Button,
+    pub next_button: Button,
+    pub back_button: Button,
+    pub error: Option<String>,
+}
+
+impl LoadMenu {
+    pub fn new(ctx: &mut Context, error: Option<String>) -> Self {
+        let save_buttons = (0..VISIBLE_SAVES).map(|i| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 200., 24.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(""),
+                [448./2. - 200./2., 44. + 28. * i as f32].into(),
+                ctx
+            )
+        }).collect();
+
+        let mut buttons = ["<", ">", "Back"].into_iter().enumerate().map(|(i, label)| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 60., 30.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: label.into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100. + 70. * i as f32, 220.].into(),
+                ctx
+            )
+        }).collect::<Vec<Button>>().into_iter();
+
+        let mut load_menu = Self {
+            saves: SavedGame::list_saves(),
+            first_save: 0,
+            save_buttons,
+            previous_button: buttons.next().unwrap(),
+            next_button: buttons.next().unwrap(),
+            back_button: buttons.next().unwrap(),
+            error,
+        };
+
+        load_menu.update_button_texts();
+
+        load_menu
+    }
+
+    fn update_button_texts(&mut self) {
+        for (i, button) in self.save_buttons.iter_mut().enumerate() {
+            let name = self.saves.get(self.first_save + i)
+                .and_then(|path| path.file_stem())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            button.text = Text::new(TextFragment {
+                text: name,
+                scale: Some(PxScale::from(14.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+impl Scene for LoadMenu {
+    fn draw(&self, _assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let mut text = Text::new("Load Game");
+
+        text.set_scale(32.);
+        let x = 448./2. - text.dimensions(ctx).unwrap().w / 2.;
+
+        canvas.draw(&text, DrawParam::new().color(Color::BLACK).dest([x, 0.]));
+
+        if self.saves.is_empty() {
+            let mut empty_text = Text::new("No saved games");
+            empty_text.set_scale(16.);
+            let x = 448./2. - empty_text.dimensions(ctx).unwrap().w / 2.;
+
+            canvas.draw(&empty_text, DrawParam::new().color(Color::BLACK).dest([x, 48.]));
+        }
+
+        for (i, button) in self.save_buttons.iter().enumerate() {
+            if self.first_save + i < self.saves.len() {
+                button.draw(canvas, ctx);
+            }
+        }
+
+        self.previous_button.draw(canvas, ctx);
+        self.next_button.draw(canvas, ctx);
+        self.back_button.draw(canvas, ctx);
+
+        if let Some(error) = &self.error {
+            let mut error_text = Text::new(error.as_str());
+            error_text.set_scale(12.);
+            error_text.set_bounds([440., 30.]);
+
+            canvas.draw(&error_text, DrawParam::new().color(Color::RED).dest([4., 256.]));
+        }
+    }
+
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _release_data: Option<(Vec2, MouseButton)>, _key_press: Option<KeyCode>, _text_input: &str, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        if let Some(data) = press_data {
+            if self.back_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenMainMenu);
+            }
+
+            if self.previous_button.is_clicked(Some(data.0)) && self.first_save > 0 {
+                self.first_save = self.first_save.saturating_sub(VISIBLE_SAVES);
+                self.update_button_texts();
+            }
+
+            if self.next_button.is_clicked(Some(data.0)) && self.first_save + VISIBLE_SAVES < self.saves.len() {
+                self.first_save += VISIBLE_SAVES;
+                self.update_button_texts();
+            }
+
+            let clicked_save = self.save_buttons.iter()
+                .position(|button| button.is_clicked(Some(data.0)))
+                .map(|i| self.first_save + i)
+                .filter(|index| *index < self.saves.len());
+
+            if let Some(index) = clicked_save {
+                let path = &self.saves[index];
+
+                match SavedGame::read(path) {
+                    Ok(saved_game) => app_events.push(AppEvent::OpenSavedGame(saved_game)),
+                    Err(error) => self.error = Some(format!("Could not read '{}': {}", path.display(), error)),
+                }
+            }
+        }
+    }
+}
@@ -1,21 +1,44 @@
-use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment}, Context};
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
 
-use crate::{app::AppEvent, widgets::Button};
+use crate::{app::AppEvent, game::saved_game::SavedGame, widgets::Button};
 
 use super::Scene;
 
 pub struct MainMenu {
-    pub play_button: Button
+    pub autosave: Option<SavedGame>,
+    pub resume_button: Button,
+    pub play_button: Button,
+    pub editor_button: Button,
+    pub puzzles_button: Button,
+    pub load_button: Button,
+    pub settings_button: Button,
 }
 
 impl MainMenu {
     pub fn new(ctx: &mut ggez::Context) -> Self{
         Self {
+            autosave: SavedGame::read_autosave(),
+            resume_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 28.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Resume".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 40.].into(),
+                ctx
+            ),
             play_button: Button::new(
                 graphics::Mesh::new_rectangle(
                     ctx,
                     graphics::DrawMode::fill(),
-                    Rect::new(0., 0., 100., 30.),
+                    Rect::new(0., 0., 100., 28.),
                     Color::GREEN
                 ).unwrap(),
                 Text::new(TextFragment {
@@ -24,31 +47,137 @@ impl MainMenu {
                     color: Some(Color::BLACK),
                     ..Default::default()
                 }),
-                [288./2. - 100./2., 152.].into(),
+                [448./2. - 100./2., 72.].into(),
+                ctx
+            ),
+            editor_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 28.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Editor".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 104.].into(),
+                ctx
+            ),
+            puzzles_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 28.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Puzzles".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 136.].into(),
+                ctx
+            ),
+            load_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 28.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Load".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 168.].into(),
+                ctx
+            ),
+            settings_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 28.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Settings".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 200.].into(),
                 ctx
-            )
+            ),
         }
     }
 }
 
 impl Scene for MainMenu {
-    fn draw(&self, _assets: &crate::assets::Assets, canvas: &mut ggez::graphics::Canvas, ctx: &mut ggez::Context) {
+    fn draw(&self, assets: &crate::assets::Assets, canvas: &mut ggez::graphics::Canvas, ctx: &mut ggez::Context) {
+        if self.autosave.is_some() {
+            self.resume_button.draw(canvas, ctx);
+        }
+
         self.play_button.draw(canvas, ctx);
+        self.editor_button.draw(canvas, ctx);
+        self.puzzles_button.draw(canvas, ctx);
+        self.load_button.draw(canvas, ctx);
+        self.settings_button.draw(canvas, ctx);
+
+        let mut theme_text = Text::new(format!("Theme: {}", assets.theme_name));
+        theme_text.set_scale(14.);
+        let theme_x = 448./2. - theme_text.dimensions(ctx).unwrap().w / 2.;
+
+        canvas.draw(&theme_text, DrawParam::new().color(Color::BLACK).dest([theme_x, 232.]));
+
+        if let Some(error) = assets.errors.first() {
+            let mut error_text = Text::new(error.as_str());
+            error_text.set_scale(12.);
+            error_text.set_bounds([440., 40.]);
+
+            canvas.draw(&error_text, DrawParam::new().color(Color::RED).dest([4., 250.]));
+        }
 
         let mut text = Text::new("Chess");
 
         text.set_scale(32.);
-        let x =  288./2. - text.dimensions(ctx).unwrap().w / 2.;
+        let x =  448./2. - text.dimensions(ctx).unwrap().w / 2.;
 
         canvas.draw(&text, DrawParam::new().color(Color::BLACK).dest([x, 0.]));
     }
 
-    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _release_data: Option<(Vec2, MouseButton)>, _key_press: Option<KeyCode>, _text_input: &str, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
         if let Some(data) = press_data {
             let is_clicked = self.play_button.is_clicked(Some(data.0));
             if is_clicked {
                 app_events.push(AppEvent::OpenNewGame);
             }
+
+            if self.autosave.is_some() && self.resume_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenSavedGame(self.autosave.clone().unwrap()));
+            }
+
+            if self.editor_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenEditor);
+            }
+
+            if self.puzzles_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenPuzzles);
+            }
+
+            if self.load_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenLoadMenu);
+            }
+
+            if self.settings_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenSettings);
+            }
         }
     }
 }
\ No newline at end of file
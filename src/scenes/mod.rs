@@ -1,9 +1,15 @@
-use ggez::{event::MouseButton, glam::Vec2, graphics::Canvas, Context};
+use ggez::{event::MouseButton, glam::Vec2, graphics::Canvas, input::keyboard::KeyCode, Context};
 use crate::{app::AppEvent, assets::Assets};
 
 pub mod main_menu;
+pub mod settings_menu;
+pub mod load_menu;
+pub mod new_game_menu;
+pub mod join_menu;
+pub mod position_editor;
+pub mod puzzle_trainer;
 
 pub trait Scene {
     fn draw(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context);
-    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, ctx: &mut Context, app_events: &mut Vec<AppEvent>);
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, release_data: Option<(Vec2, MouseButton)>, key_press: Option<KeyCode>, text_input: &str, ctx: &mut Context, app_events: &mut Vec<AppEvent>);
 }
\ No newline at end of file
@@ -0,0 +1,314 @@
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+use rand::Rng;
+
+use crate::{app::AppEvent, assets::Assets, engine::MAX_LEVEL, game::{fen, game_config::{AnalysisEngine, GameConfig, Opponent, PlayerKind, StartPosition, Variant}, piece::Player}, network::protocol::DEFAULT_PORT, settings::{Settings, TimeControl, TIME_CONTROLS}, widgets::{Button, TextField}};
+
+use super::Scene;
+
+const OPTIONS: [&str; 5] = [
+    "Opponent",
+    "Colour",
+    "Time control",
+    "Variant",
+    "Start position",
+];
+
+const EXTERNAL_OPPONENT: u8 = MAX_LEVEL + 1;
+const LAN_HOST: u8 = MAX_LEVEL + 2;
+const LAN_JOIN: u8 = MAX_LEVEL + 3;
+const ANALYSIS: u8 = MAX_LEVEL + 4;
+
+pub struct NewGameMenu {
+    pub opponent: u8,
+    pub player_color: Option<Player>,
+    // the engine plays both sides
+    pub is_watching: bool,
+    pub time_control: Option<TimeControl>,
+    pub variant: Variant,
+    pub custom_start: bool,
+    pub option_buttons: Vec<Button>,
+    pub engine_path_field: TextField,
+    pub start_field: TextField,
+    pub start_button: Button,
+    pub back_button: Button,
+    pub error: Option<String>,
+}
+
+impl NewGameMenu {
+    pub fn new(ctx: &mut Context, settings: &Settings, error: Option<String>) -> Self {
+        let option_buttons = (0..OPTIONS.len()).map(|i| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 22.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(""),
+                [200., 40. + 26. * i as f32].into(),
+                ctx
+            )
+        }).collect();
+
+        let mut buttons = ["Start", "Back"].into_iter().enumerate().map(|(i, label)| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 30.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: label.into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 105. + 110. * i as f32, 226.].into(),
+                ctx
+            )
+        }).collect::<Vec<Button>>().into_iter();
+
+        let mut new_game_menu = Self {
+            opponent: 0,
+            player_color: Some(Player::White),
+            is_watching: false,
+            time_control: settings.time_control,
+            variant: Variant::Standard,
+            custom_start: false,
+            option_buttons,
+            engine_path_field: TextField::new("", [306., 41.].into(), 138.),
+            start_field: TextField::new("", [306., 145.].into(), 138.),
+            start_button: buttons.next().unwrap(),
+            back_button: buttons.next().unwrap(),
+            error,
+        };
+
+        new_game_menu.update_button_texts();
+
+        new_game_menu
+    }
+
+    pub fn with_fen(ctx: &mut Context, settings: &Settings, fen: String) -> Self {
+        let mut new_game_menu = Self::new(ctx, settings, None);
+
+        new_game_menu.custom_start = true;
+        new_game_menu.start_field.text = fen;
+        new_game_menu.update_button_texts();
+
+        new_game_menu
+    }
+
+    fn get_option_value(&self, option: usize) -> String {
+        match option {
+            0 => match self.opponent {
+                0 => "Human".into(),
+                EXTERNAL_OPPONENT => "External".into(),
+                LAN_HOST => "LAN host".into(),
+                LAN_JOIN => "LAN join".into(),
+                ANALYSIS => "Analysis".into(),
+                level => format!("Engine {}", level),
+            },
+            1 => match self.player_color {
+                _ if self.is_watching => "Watch".into(),
+                Some(player) => format!("{:?}", player),
+                None => "Random".into(),
+            },
+            2 => match self.time_control {
+                Some(time_control) => format!("{}+{}", time_control.minutes, time_control.increment),
+                None => "Untimed".into(),
+            },
+            3 => format!("{:?}", self.variant),
+            _ => match (self.variant, self.custom_start) {
+                (Variant::Standard, false) => "Standard".into(),
+                (Variant::Standard, true) => "FEN".into(),
+                (Variant::Chess960, false) => "Random".into(),
+                (Variant::Chess960, true) => "Number".into(),
+            },
+        }
+    }
+
+    fn change_option(&mut self, option: usize) {
+        match option {
+            0 => {
+                self.opponent = (self.opponent + 1) % (ANALYSIS + 1);
+
+                // the field holds a port or address for these instead of an engine path
+                if self.opponent == LAN_HOST || self.opponent == LAN_JOIN {
+                    self.engine_path_field.text.clear();
+                }
+            },
+            1 => (self.player_color, self.is_watching) = match self.player_color {
+                _ if self.is_watching => (Some(Player::White), false),
+                Some(Player::White) => (Some(Player::Black), false),
+                Some(Player::Black) => (None, false),
+                None => (None, true),
+            },
+            2 => {
+                let index = TIME_CONTROLS.iter().position(|time_control| *time_control == self.time_control).unwrap_or(0);
+
+                self.time_control = TIME_CONTROLS[(index + 1) % TIME_CONTROLS.len()];
+            },
+            3 => {
+                self.variant = if self.variant == Variant::Standard { Variant::Chess960 } else { Variant::Standard };
+                self.custom_start = false;
+                self.start_field.text.clear();
+            },
+            _ => self.custom_start = !self.custom_start,
+        }
+    }
+
+    fn update_button_texts(&mut self) {
+        for option in 0..OPTIONS.len() {
+            self.option_buttons[option].text = Text::new(TextFragment {
+                text: self.get_option_value(option),
+                scale: Some(PxScale::from(14.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            });
+        }
+    }
+
+    fn get_config(&self) -> Result<GameConfig, String> {
+        let mut rng = rand::thread_rng();
+
+        let engine_path = self.engine_path_field.text.trim();
+
+        let opponent = match self.opponent {
+            0 | ANALYSIS => Opponent::Human,
+            EXTERNAL_OPPONENT => {
+                if engine_path.is_empty() {
+                    return Err("Enter the path of a UCI engine".into());
+                }
+
+                Opponent::External(engine_path.into())
+            },
+            LAN_HOST if engine_path.is_empty() => Opponent::LanHost(DEFAULT_PORT),
+            LAN_HOST => Opponent::LanHost(engine_path.parse().map_err(|_| "Enter the port to host on")?),
+            LAN_JOIN => {
+                if engine_path.is_empty() {
+                    return Err("Enter the address of the host".into());
+                }
+
+                Opponent::LanJoin(engine_path.into())
+            },
+            level => Opponent::Engine(level),
+        };
+
+        let start_position = match (self.variant, self.custom_start) {
+            (Variant::Standard, false) => StartPosition::Standard,
+            (Variant::Standard, true) => {
+                let fen = self.start_field.text.trim();
+
+                fen::parse_fen(fen).map_err(|error| format!("Invalid FEN: {}", error))?;
+
+                StartPosition::Fen(fen.into())
+            },
+            (Variant::Chess960, false) => StartPosition::Chess960(rng.gen_range(0..960)),
+            (Variant::Chess960, true) => {
+                let number = self.start_field.text.trim().parse::<u32>()
+                    .ok()
+                    .filter(|number| *number < 960)
+                    .ok_or("Enter a Chess960 position number from 0 to 959")?;
+
+                StartPosition::Chess960(number)
+            },
+        };
+
+        let players = match &opponent {
+            _ if !self.is_watching => None,
+            Opponent::Engine(level) => Some([PlayerKind::Engine(*level), PlayerKind::Engine(*level)]),
+            Opponent::External(path) => Some([PlayerKind::External(path.clone()), PlayerKind::External(path.clone())]),
+            _ => return Err("Only an engine can play both sides".into()),
+        };
+
+        Ok(GameConfig {
+            opponent,
+            player_color: self.player_color.unwrap_or(if rng.gen() { Player::White } else { Player::Black }),
+            time_control: self.time_control,
+            variant: self.variant,
+            start_position,
+            analysis: match self.opponent {
+                ANALYSIS if engine_path.is_empty() => Some(AnalysisEngine::BuiltIn),
+                ANALYSIS => Some(AnalysisEngine::External(engine_path.into())),
+                _ => None,
+            },
+            players,
+        })
+    }
+}
+
+impl Scene for NewGameMenu {
+    fn draw(&self, _assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let mut text = Text::new("New Game");
+
+        text.set_scale(32.);
+        let x = 448./2. - text.dimensions(ctx).unwrap().w / 2.;
+
+        canvas.draw(&text, DrawParam::new().color(Color::BLACK).dest([x, 0.]));
+
+        for (i, label) in OPTIONS.iter().enumerate() {
+            let mut label_text = Text::new(*label);
+            label_text.set_scale(16.);
+
+            let button = &self.option_buttons[i];
+            let y = button.position.y + button.background_dimensions.h / 2. - label_text.dimensions(ctx).unwrap().h / 2.;
+
+            canvas.draw(&label_text, DrawParam::new().color(Color::BLACK).dest([80., y]));
+
+            button.draw(canvas, ctx);
+        }
+
+        if self.opponent >= EXTERNAL_OPPONENT {
+            self.engine_path_field.draw(canvas, ctx);
+        }
+
+        if self.custom_start {
+            self.start_field.draw(canvas, ctx);
+        }
+
+        self.start_button.draw(canvas, ctx);
+        self.back_button.draw(canvas, ctx);
+
+        if let Some(error) = &self.error {
+            let mut error_text = Text::new(error.as_str());
+            error_text.set_scale(12.);
+            error_text.set_bounds([440., 30.]);
+
+            canvas.draw(&error_text, DrawParam::new().color(Color::RED).dest([4., 262.]));
+        }
+    }
+
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _release_data: Option<(Vec2, MouseButton)>, key_press: Option<KeyCode>, text_input: &str, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        let press_position = press_data.map(|(position, _)| position);
+
+        if self.opponent >= EXTERNAL_OPPONENT {
+            self.engine_path_field.update(press_position, text_input, key_press);
+        }
+
+        if self.custom_start {
+            self.start_field.update(press_position, text_input, key_press);
+        }
+
+        if let Some(data) = press_data {
+            if self.back_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenMainMenu);
+            }
+
+            if self.start_button.is_clicked(Some(data.0)) {
+                match self.get_config() {
+                    Ok(GameConfig { opponent: Opponent::LanJoin(address), .. }) => app_events.push(AppEvent::JoinGame(address)),
+                    Ok(config) => app_events.push(AppEvent::StartGame(config)),
+                    Err(error) => self.error = Some(error),
+                }
+            }
+
+            let clicked_option = self.option_buttons.iter().position(|button| button.is_clicked(Some(data.0)));
+
+            if let Some(option) = clicked_option {
+                self.change_option(option);
+                self.update_button_texts();
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+
+use crate::{app::AppEvent, assets::Assets, game::{board::Board, fen, game_config::{AnalysisEngine, GameConfig, StartPosition}, piece::{PieceType, Player}, position::Position, Game}, widgets::{Button, TextField}};
+
+use super::Scene;
+
+const PALETTE: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+const CASTLING: [char; 4] = ['K', 'Q', 'k', 'q'];
+
+pub struct PositionEditor {
+    pub board: Board,
+    pub current_player: Player,
+    pub castling: [bool; 4],
+    pub en_passant_column: Option<i32>,
+    pub selected_piece: Option<(Player, PieceType)>,
+    pub side_button: Button,
+    pub en_passant_button: Button,
+    pub castling_buttons: Vec<Button>,
+    pub clear_button: Button,
+    pub reset_button: Button,
+    pub fen_field: TextField,
+    pub load_button: Button,
+    pub copy_button: Button,
+    pub paste_button: Button,
+    pub back_button: Button,
+    pub play_button: Button,
+    pub analyse_button: Button,
+    pub error: Option<String>,
+}
+
+impl PositionEditor {
+    pub fn new(ctx: &mut Context) -> Self {
+        let mut new_button = |label: &str, width: f32, position: [f32; 2]| Button::new(
+            graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(0., 0., width, 22.),
+                Color::GREEN
+            ).unwrap(),
+            Text::new(TextFragment {
+                text: label.into(),
+                scale: Some(PxScale::from(14.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            }),
+            position.into(),
+            ctx
+        );
+
+        let mut position_editor = Self {
+            board: Board::new(),
+            current_player: Player::White,
+            castling: [true; 4],
+            en_passant_column: None,
+            selected_piece: None,
+            side_button: new_button("", 74., [292., 60.]),
+            en_passant_button: new_button("", 74., [370., 60.]),
+            castling_buttons: CASTLING.iter().enumerate().map(|(i, _)| new_button("", 35., [292. + 39. * i as f32, 86.])).collect(),
+            clear_button: new_button("Clear", 74., [292., 112.]),
+            reset_button: new_button("Reset", 74., [370., 112.]),
+            fen_field: TextField::new("", [292., 140.].into(), 152.),
+            load_button: new_button("Load FEN", 74., [292., 164.]),
+            copy_button: new_button("Copy", 74., [370., 164.]),
+            paste_button: new_button("Paste", 74., [292., 190.]),
+            back_button: new_button("Back", 74., [370., 190.]),
+            play_button: new_button("Play", 74., [292., 216.]),
+            analyse_button: new_button("Analyse", 74., [370., 216.]),
+            error: None,
+        };
+
+        position_editor.update_button_texts();
+        position_editor.update_fen_field();
+
+        position_editor
+    }
+
+    fn get_palette_coordinates(player: Player, index: usize) -> [f32; 2] {
+        [292. + 26. * index as f32, if player == Player::White { 4. } else { 30. }]
+    }
+
+    fn get_square_coordinates(position: Position) -> [f32; 2] {
+        [(32 * position.column + 16) as f32, (32 * position.row + 16) as f32]
+    }
+
+    fn get_square_at(screen_position: Vec2) -> Option<Position> {
+        if screen_position.x > 16. && screen_position.x < 272.
+        && screen_position.y > 16. && screen_position.y < 272. {
+            return Some(Position::new(((screen_position.x - 16.) / 32.) as i32, ((screen_position.y - 16.) / 32.) as i32));
+        }
+
+        None
+    }
+
+    fn get_palette_piece_at(screen_position: Vec2) -> Option<(Player, PieceType)> {
+        for player in [Player::White, Player::Black] {
+            for (index, piece_type) in PALETTE.iter().enumerate() {
+                let [x, y] = Self::get_palette_coordinates(player, index);
+
+                if screen_position.x > x && screen_position.x < x + 24.
+                && screen_position.y > y && screen_position.y < y + 24. {
+                    return Some((player, *piece_type));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn get_fen(&self) -> String {
+        let castling: String = CASTLING.iter()
+            .zip(self.castling)
+            .filter(|(_, allowed)| *allowed)
+            .map(|(letter, _)| *letter)
+            .collect();
+
+        let en_passant = self.en_passant_column
+            .map(|column| Position::new(column, if self.current_player == Player::White { 2 } else { 5 }).to_algebraic())
+            .unwrap_or("-".into());
+
+        format!(
+            "{} {} {} {} 0 1",
+            fen::get_placement(&self.board),
+            if self.current_player == Player::White { "w" } else { "b" },
+            if castling.is_empty() { "-".into() } else { castling },
+            en_passant
+        )
+    }
+
+    fn load_fen(&mut self, fen: &str) {
+        let position = match fen::parse_fen(fen) {
+            Ok(position) => position,
+            Err(error) => {
+                self.error = Some(format!("Invalid FEN: {}", error));
+                return;
+            },
+        };
+
+        let castling = fen.split_whitespace().nth(2).unwrap_or("-");
+
+        self.board = position.board;
+        self.current_player = position.current_player;
+        self.castling = CASTLING.map(|letter| castling.contains(letter));
+        self.en_passant_column = self.board.en_passant.map(|position| position.column);
+        self.error = None;
+
+        self.update_button_texts();
+        self.update_fen_field();
+    }
+
+    // checks the position and returns the fen a game can be started from
+    fn get_valid_fen(&self) -> Result<String, String> {
+        let fen = self.get_fen();
+
+        fen::parse_fen(&fen)?;
+
+        if let Some(column) = self.en_passant_column {
+            let pawn_row = if self.current_player == Player::White { 3 } else { 4 };
+            let has_pawn = self.board[Position::new(column, pawn_row)].as_ref()
+                .is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn && piece.get_color() != self.current_player);
+
+            if !has_pawn {
+                return Err("no pawn could have just moved past the en passant square".into());
+            }
+        }
+
+        Ok(fen)
+    }
+
+    fn update_button_texts(&mut self) {
+        let label = |text: String, is_active: bool| Text::new(TextFragment {
+            text,
+            scale: Some(PxScale::from(14.0)),
+            color: Some(if is_active { Color::BLACK } else { Color::new(0.4, 0.4, 0.4, 1.) }),
+            ..Default::default()
+        });
+
+        self.side_button.text = label(format!("{:?}", self.current_player), true);
+        self.en_passant_button.text = label(
+            match self.en_passant_column {
+                Some(column) => format!("e.p. {}", (b'a' + column as u8) as char),
+                None => "e.p. -".into(),
+            },
+            self.en_passant_column.is_some()
+        );
+
+        for (i, button) in self.castling_buttons.iter_mut().enumerate() {
+            button.text = label(CASTLING[i].to_string(), self.castling[i]);
+        }
+    }
+
+    fn update_fen_field(&mut self) {
+        self.fen_field.text = self.get_fen();
+    }
+
+    fn start_game(&mut self, app_events: &mut Vec<AppEvent>, analyse: bool) {
+        match self.get_valid_fen() {
+            Ok(fen) if analyse => app_events.push(AppEvent::StartGame(GameConfig {
+                start_position: StartPosition::Fen(fen),
+                analysis: Some(AnalysisEngine::BuiltIn),
+                ..Default::default()
+            })),
+            Ok(fen) => app_events.push(AppEvent::SetUpPosition(fen)),
+            Err(error) => self.error = Some(format!("Invalid position: {}", error)),
+        }
+    }
+}
+
+impl Scene for PositionEditor {
+    fn draw(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        canvas.draw(assets.graphics.get("board").unwrap(), [0., 0.]);
+
+        for column in 0..8 {
+            for row in 0..8 {
+                let pos = Position::new(column, row);
+
+                if let Some(piece) = &self.board[pos] {
+                    canvas.draw(
+                        assets.graphics.get(&Game::get_piece_asset_id(piece.get_color(), piece.get_piece_type())).unwrap(),
+                        Self::get_square_coordinates(pos)
+                    );
+                }
+            }
+        }
+
+        for player in [Player::White, Player::Black] {
+            for (index, piece_type) in PALETTE.iter().enumerate() {
+                let coordinates = Self::get_palette_coordinates(player, index);
+
+                if self.selected_piece == Some((player, *piece_type)) {
+                    canvas.draw(&graphics::Mesh::new_rectangle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        Rect::new(0., 0., 24., 24.),
+                        Color::new(0.2, 0.6, 1., 0.5)
+                    ).unwrap(), coordinates);
+                }
+
+                canvas.draw(
+                    assets.graphics.get(&Game::get_piece_asset_id(player, *piece_type)).unwrap(),
+                    DrawParam::new()
+                        .dest(coordinates)
+                        .scale([0.75, 0.75])
+                );
+            }
+        }
+
+        self.side_button.draw(canvas, ctx);
+        self.en_passant_button.draw(canvas, ctx);
+
+        for button in self.castling_buttons.iter() {
+            button.draw(canvas, ctx);
+        }
+
+        self.clear_button.draw(canvas, ctx);
+        self.reset_button.draw(canvas, ctx);
+        self.fen_field.draw(canvas, ctx);
+        self.load_button.draw(canvas, ctx);
+        self.copy_button.draw(canvas, ctx);
+        self.paste_button.draw(canvas, ctx);
+        self.back_button.draw(canvas, ctx);
+        self.play_button.draw(canvas, ctx);
+        self.analyse_button.draw(canvas, ctx);
+
+        if let Some(error) = &self.error {
+            let mut error_text = Text::new(error.as_str());
+            error_text.set_scale(12.);
+            error_text.set_bounds([156., 46.]);
+
+            canvas.draw(&error_text, DrawParam::new().color(Color::RED).dest([292., 242.]));
+        }
+    }
+
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _release_data: Option<(Vec2, MouseButton)>, key_press: Option<KeyCode>, text_input: &str, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        self.fen_field.update(press_data.map(|(position, _)| position), text_input, key_press);
+
+        if self.fen_field.is_focused && key_press == Some(KeyCode::Return) {
+            let fen = self.fen_field.text.clone();
+            self.load_fen(&fen);
+        }
+
+        let Some((press_position, button)) = press_data else {
+            return;
+        };
+
+        let mut changed = false;
+
+        if let Some(pos) = Self::get_square_at(press_position) {
+            let placed = match (button, self.selected_piece) {
+                (MouseButton::Left, Some((player, piece_type))) => {
+                    let is_same = self.board[pos].as_ref().is_some_and(|piece| piece.get_color() == player && piece.get_piece_type() == piece_type);
+
+                    if is_same { None } else { Some(Board::new_piece(player, piece_type)) }
+                },
+                _ => None,
+            };
+
+            self.board[pos] = placed;
+            changed = true;
+        }
+
+        if let Some(piece) = Self::get_palette_piece_at(press_position) {
+            self.selected_piece = if self.selected_piece == Some(piece) { None } else { Some(piece) };
+        }
+
+        if self.side_button.is_clicked(Some(press_position)) {
+            self.current_player = self.current_player.get_opponent();
+            self.en_passant_column = None;
+            changed = true;
+        }
+
+        if self.en_passant_button.is_clicked(Some(press_position)) {
+            self.en_passant_column = match self.en_passant_column {
+                None => Some(0),
+                Some(7) => None,
+                Some(column) => Some(column + 1),
+            };
+            changed = true;
+        }
+
+        if let Some(i) = self.castling_buttons.iter().position(|button| button.is_clicked(Some(press_position))) {
+            self.castling[i] = !self.castling[i];
+            changed = true;
+        }
+
+        if self.clear_button.is_clicked(Some(press_position)) {
+            self.board = Board::default();
+            self.castling = [false; 4];
+            self.en_passant_column = None;
+            changed = true;
+        }
+
+        if self.reset_button.is_clicked(Some(press_position)) {
+            self.board = Board::new();
+            self.current_player = Player::White;
+            self.castling = [true; 4];
+            self.en_passant_column = None;
+            changed = true;
+        }
+
+        if self.load_button.is_clicked(Some(press_position)) {
+            let fen = self.fen_field.text.clone();
+            self.load_fen(&fen);
+        }
+
+        if self.copy_button.is_clicked(Some(press_position)) {
+            let result = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(self.get_fen()));
+
+            if let Err(error) = result {
+                self.error = Some(format!("Could not copy: {}", error));
+            }
+        }
+
+        if self.paste_button.is_clicked(Some(press_position)) {
+            match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+                Ok(text) => self.load_fen(text.trim()),
+                Err(error) => self.error = Some(format!("Could not paste: {}", error)),
+            }
+        }
+
+        if self.back_button.is_clicked(Some(press_position)) {
+            app_events.push(AppEvent::OpenMainMenu);
+        }
+
+        if self.play_button.is_clicked(Some(press_position)) {
+            self.start_game(app_events, false);
+        }
+
+        if self.analyse_button.is_clicked(Some(press_position)) {
+            self.start_game(app_events, true);
+        }
+
+        if changed {
+            self.error = None;
+            self.update_button_texts();
+            self.update_fen_field();
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+
+use crate::{app::AppEvent, assets::Assets, game::{game_config::{GameConfig, StartPosition}, notation, piece::Player, player_controller::ReplayController, position::Position, puzzle::{Puzzle, PuzzleStats}, Game}, settings::Settings, widgets::Button};
+
+use super::Scene;
+
+const PANEL_X: f32 = 292.;
+const PANEL_Y: f32 = 130.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PuzzleStatus {
+    Solving,
+    Solved(f32),
+    Failed(f32),
+}
+
+pub struct PuzzleTrainer {
+    pub settings: Settings,
+    pub stats: PuzzleStats,
+    pub puzzle: Option<Puzzle>,
+    pub game: Option<Game>,
+    pub status: PuzzleStatus,
+    // plies of the game already compared against the solution
+    pub checked_plies: usize,
+    pub best_move: Option<(String, Position, Position)>,
+    pub played_ids: Vec<String>,
+    pub error: Option<String>,
+    pub next_button: Button,
+    pub back_button: Button,
+}
+
+impl PuzzleTrainer {
+    pub fn new(ctx: &mut Context, settings: &Settings) -> Self {
+        let mut new_button = |label: &str, position: [f32; 2]| Button::new(
+            graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(0., 0., 74., 20.),
+                Color::GREEN
+            ).unwrap(),
+            Text::new(TextFragment {
+                text: label.into(),
+                scale: Some(PxScale::from(12.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            }),
+            position.into(),
+            ctx
+        );
+
+        let mut puzzle_trainer = Self {
+            settings: settings.clone(),
+            stats: PuzzleStats::load(),
+            puzzle: None,
+            game: None,
+            status: PuzzleStatus::Solving,
+            checked_plies: 0,
+            best_move: None,
+            played_ids: Vec::new(),
+            error: None,
+            next_button: new_button("Next", [PANEL_X, 214.]),
+            back_button: new_button("Back", [PANEL_X + 78., 214.]),
+        };
+
+        puzzle_trainer.load_next(ctx);
+
+        puzzle_trainer
+    }
+
+    pub fn load_next(&mut self, ctx: &mut Context) {
+        self.puzzle = None;
+        self.game = None;
+        self.status = PuzzleStatus::Solving;
+        self.checked_plies = 0;
+        self.best_move = None;
+        self.error = None;
+
+        let result = Puzzle::pick(self.stats.rating, &self.played_ids).and_then(|puzzle| {
+            let game = self.start_puzzle(ctx, &puzzle).map_err(|error| format!("puzzle {} is invalid: {}", puzzle.id, error))?;
+
+            Ok((puzzle, game))
+        });
+
+        match result {
+            Ok((puzzle, game)) => {
+                self.played_ids.push(puzzle.id.clone());
+                self.puzzle = Some(puzzle);
+                self.game = Some(game);
+            },
+            Err(error) => self.error = Some(format!("Could not load puzzle: {}", error)),
+        }
+    }
+
+    fn start_puzzle(&self, ctx: &mut Context, puzzle: &Puzzle) -> Result<Game, String> {
+        let config = GameConfig {
+            start_position: StartPosition::Fen(puzzle.fen.clone()),
+            ..GameConfig::default()
+        };
+
+        let mut game = Game::new(ctx, &self.settings, config)?;
+
+        if let Some(uci) = puzzle.moves.get(notation::get_san_line(&game.board, game.start_player, &puzzle.moves).len()) {
+            return Err(format!("illegal move '{}'", uci));
+        }
+
+        // the position is set up for the opponent's first move, which plays its side of the solution
+        let solver = game.start_player.get_opponent();
+
+        game.is_puzzle = true;
+        game.set_controller(game.start_player, Box::new(ReplayController::new(puzzle.moves.clone(), Duration::ZERO)));
+        game.flipped = self.settings.flipped != (solver == Player::Black);
+        game.update_move_list_layout();
+
+        Ok(game)
+    }
+
+    fn is_solution_move(game: &Game, ply: usize, uci: &str) -> bool {
+        let Some((from_pos, to_pos, promotion)) = notation::parse_uci(uci) else {
+            return false;
+        };
+
+        let (board, player) = game.get_position(ply);
+        let entry = &game.history[ply];
+
+        board.find_move(player, from_pos, to_pos)
+            .is_some_and(|piece_move| piece_move.get_from_pos() == entry.from_pos && piece_move.get_to_pos() == entry.to_pos)
+            && promotion == entry.promotion
+    }
+
+    fn check_solution(&mut self) {
+        let (Some(game), Some(puzzle)) = (self.game.as_ref(), self.puzzle.as_ref()) else {
+            return;
+        };
+
+        let mut result = None;
+
+        while result.is_none() && self.checked_plies < game.history.len().min(puzzle.moves.len()) {
+            let ply = self.checked_plies;
+            self.checked_plies += 1;
+
+            // even plies are the opponent's, played from the solution
+            let is_solver_move = ply % 2 == 1;
+
+            if !is_solver_move {
+                continue;
+            }
+
+            let entry = &game.history[ply];
+            let is_last = ply + 1 == puzzle.moves.len();
+
+            // any mate is accepted on the final move
+            let is_mate = entry.is_checked && entry.board.get_legal_moves(entry.current_player).is_empty();
+            let is_correct = Self::is_solution_move(game, ply, &puzzle.moves[ply]) || is_last && is_mate;
+
+            if !is_correct || is_last {
+                result = Some((is_correct, ply));
+            }
+        }
+
+        let Some((is_solved, ply)) = result else {
+            return;
+        };
+
+        if !is_solved {
+            let (board, player) = game.get_position(ply);
+            let san = notation::get_san_line(board, player, &puzzle.moves[ply..=ply]).into_iter().next();
+
+            if let (Some(san), Some((from_pos, to_pos, _))) = (san, notation::parse_uci(&puzzle.moves[ply])) {
+                self.best_move = Some((san, from_pos, to_pos));
+            }
+        }
+
+        let change = self.stats.record(puzzle.rating, is_solved);
+        self.status = if is_solved { PuzzleStatus::Solved(change) } else { PuzzleStatus::Failed(change) };
+
+        if let Some(game) = self.game.as_mut() {
+            // the opponent stops following the solution
+            game.set_controller(game.start_player, Box::new(ReplayController::new(Vec::new(), Duration::ZERO)));
+
+            game.selected_position = None;
+            game.legal_moves = None;
+            game.premoves.clear();
+        }
+    }
+
+    fn get_panel_lines(&self) -> Vec<String> {
+        let Some(game) = &self.game else {
+            return Vec::new();
+        };
+
+        let solver = game.start_player.get_opponent();
+        let rating = format!("Rating {:.0}", self.stats.rating);
+
+        let mut lines = match self.status {
+            PuzzleStatus::Solving => vec![rating],
+            PuzzleStatus::Solved(change) | PuzzleStatus::Failed(change) => vec![format!("{} ({:+.0})", rating, change)],
+        };
+
+        lines.push(format!("Streak {}, best {}", self.stats.streak, self.stats.best_streak));
+
+        lines.push(match self.status {
+            PuzzleStatus::Solving if self.checked_plies > 1 => String::from("Correct, keep going"),
+            PuzzleStatus::Solving => format!("{:?} to play", solver),
+            PuzzleStatus::Solved(_) => String::from("Solved!"),
+            PuzzleStatus::Failed(_) => match &self.best_move {
+                Some((san, _, _)) => format!("Wrong, {} was best", san),
+                None => String::from("Wrong move"),
+            },
+        });
+
+        if let (Some(puzzle), false) = (&self.puzzle, self.status == PuzzleStatus::Solving) {
+            lines.push(format!("Puzzle {} ({})", puzzle.id, puzzle.rating));
+            lines.push(puzzle.themes.join(", "));
+        }
+
+        lines
+    }
+}
+
+impl Scene for PuzzleTrainer {
+    fn draw(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        match &self.game {
+            Some(game) => game.draw(assets, canvas, ctx),
+            None => canvas.draw(assets.graphics.get("board").unwrap(), [0., 0.]),
+        }
+
+        if let (Some(game), Some((_, from_pos, to_pos))) = (&self.game, &self.best_move) {
+            if game.viewed_ply.is_none() {
+                game.draw_arrow(*from_pos, *to_pos, Color::new(0.1, 0.7, 0.2, 0.7), canvas, ctx);
+            }
+        }
+
+        for (i, line) in self.get_panel_lines().iter().enumerate() {
+            let mut line_text = Text::new(line.as_str());
+            line_text.set_scale(12.);
+            line_text.set_bounds([152., 14.]);
+
+            canvas.draw(&line_text, DrawParam::new().dest([PANEL_X, PANEL_Y + 16. * i as f32]).color(Color::BLACK));
+        }
+
+        if let Some(error) = &self.error {
+            let mut error_text = Text::new(error.as_str());
+            error_text.set_scale(12.);
+            error_text.set_bounds([152., 80.]);
+
+            canvas.draw(&error_text, DrawParam::new().dest([PANEL_X, PANEL_Y]).color(Color::RED));
+        }
+
+        if self.status != PuzzleStatus::Solving || self.game.is_none() {
+            self.next_button.draw(canvas, ctx);
+        }
+
+        self.back_button.draw(canvas, ctx);
+    }
+
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, release_data: Option<(Vec2, MouseButton)>, key_press: Option<KeyCode>, text_input: &str, ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        let press_position = press_data.map(|(position, _)| position);
+
+        if self.back_button.is_clicked(press_position) {
+            app_events.push(AppEvent::OpenMainMenu);
+
+            return;
+        }
+
+        if (self.status != PuzzleStatus::Solving || self.game.is_none()) && self.next_button.is_clicked(press_position) {
+            self.load_next(ctx);
+
+            return;
+        }
+
+        let is_finished = self.status != PuzzleStatus::Solving;
+
+        if let Some(game) = self.game.as_mut() {
+            // a finished puzzle can only be browsed
+            let press_data = press_data.filter(|(position, _)| !is_finished || game.get_square_at(*position).is_none());
+
+            game.update(press_data, release_data, key_press, text_input, ctx, app_events);
+        }
+
+        self.check_solution();
+    }
+}
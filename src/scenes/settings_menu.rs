@@ -0,0 +1,175 @@
+use ggez::{event::MouseButton, glam::Vec2, graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment}, input::keyboard::KeyCode, Context};
+
+use crate::{app::AppEvent, assets::Assets, settings::Settings, widgets::Button};
+
+use super::Scene;
+
+const OPTIONS: [&str; 11] = [
+    "Theme",
+    "Orientation",
+    "Animations",
+    "Sound",
+    "Auto-queen",
+    "Confirm moves",
+    "Move input",
+    "Time control",
+    "Show threats",
+    "Threads",
+    "Hash size",
+];
+
+// the options fill two columns of labels and buttons
+const OPTIONS_PER_COLUMN: usize = 6;
+const LABEL_WIDTH: f32 = 104.;
+
+pub struct SettingsMenu {
+    pub settings: Settings,
+    pub themes: Vec<String>,
+    pub option_buttons: Vec<Button>,
+    pub back_button: Button,
+}
+
+impl SettingsMenu {
+    pub fn new(ctx: &mut Context, settings: Settings) -> Self {
+        let option_buttons = (0..OPTIONS.len()).map(|i| {
+            Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 24.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(""),
+                [8. + LABEL_WIDTH + 224. * (i / OPTIONS_PER_COLUMN) as f32, 40. + 30. * (i % OPTIONS_PER_COLUMN) as f32].into(),
+                ctx
+            )
+        }).collect();
+
+        let mut settings_menu = Self {
+            settings,
+            themes: Assets::get_theme_names(ctx),
+            option_buttons,
+            back_button: Button::new(
+                graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    Rect::new(0., 0., 100., 30.),
+                    Color::GREEN
+                ).unwrap(),
+                Text::new(TextFragment {
+                    text: "Back".into(),
+                    scale: Some(PxScale::from(16.0)),
+                    color: Some(Color::BLACK),
+                    ..Default::default()
+                }),
+                [448./2. - 100./2., 248.].into(),
+                ctx
+            ),
+        };
+
+        settings_menu.update_button_texts();
+
+        settings_menu
+    }
+
+    fn get_option_value(&self, option: usize) -> String {
+        let settings = &self.settings;
+
+        match option {
+            0 => settings.theme.clone(),
+            1 => if settings.flipped { "Black".into() } else { "White".into() },
+            2 => format!("{:?}", settings.animation_speed),
+            3 => settings.get_volume_name(),
+            4 => if settings.auto_queen { "On".into() } else { "Off".into() },
+            5 => if settings.confirm_moves { "On".into() } else { "Off".into() },
+            6 => if settings.keyboard_moves { "Keyboard".into() } else { "Mouse".into() },
+            7 => settings.get_time_control_name(),
+            8 => if settings.show_threats { "On".into() } else { "Off".into() },
+            9 => settings.threads.to_string(),
+            _ => format!("{} MB", settings.hash_size),
+        }
+    }
+
+    fn change_option(&mut self, option: usize) {
+        let settings = &mut self.settings;
+
+        match option {
+            0 => {
+                let index = self.themes.iter().position(|theme| *theme == settings.theme).map(|index| index + 1).unwrap_or(0);
+
+                settings.theme = self.themes[index % self.themes.len()].clone();
+            },
+            1 => settings.flipped = !settings.flipped,
+            2 => settings.animation_speed = settings.animation_speed.next(),
+            3 => settings.next_volume(),
+            4 => settings.auto_queen = !settings.auto_queen,
+            5 => settings.confirm_moves = !settings.confirm_moves,
+            6 => settings.keyboard_moves = !settings.keyboard_moves,
+            7 => settings.next_time_control(),
+            8 => settings.show_threats = !settings.show_threats,
+            9 => settings.next_threads(),
+            _ => settings.next_hash_size(),
+        }
+    }
+
+    fn update_button_texts(&mut self) {
+        for option in 0..OPTIONS.len() {
+            self.option_buttons[option].text = Text::new(TextFragment {
+                text: self.get_option_value(option),
+                scale: Some(PxScale::from(14.0)),
+                color: Some(Color::BLACK),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+impl Scene for SettingsMenu {
+    fn draw(&self, assets: &Assets, canvas: &mut Canvas, ctx: &mut Context) {
+        let mut text = Text::new("Settings");
+
+        text.set_scale(32.);
+        let x = 448./2. - text.dimensions(ctx).unwrap().w / 2.;
+
+        canvas.draw(&text, DrawParam::new().color(Color::BLACK).dest([x, 0.]));
+
+        for (i, label) in OPTIONS.iter().enumerate() {
+            let mut label_text = Text::new(*label);
+            label_text.set_scale(16.);
+
+            let button = &self.option_buttons[i];
+            let y = button.position.y + button.background_dimensions.h / 2. - label_text.dimensions(ctx).unwrap().h / 2.;
+
+            canvas.draw(&label_text, DrawParam::new().color(Color::BLACK).dest([button.position.x - LABEL_WIDTH, y]));
+
+            button.draw(canvas, ctx);
+        }
+
+        self.back_button.draw(canvas, ctx);
+
+        if let Some(error) = assets.errors.first() {
+            let mut error_text = Text::new(error.as_str());
+            error_text.set_scale(12.);
+            error_text.set_bounds([440., 40.]);
+
+            canvas.draw(&error_text, DrawParam::new().color(Color::RED).dest([4., 278.]));
+        }
+    }
+
+    fn update(&mut self, press_data: Option<(Vec2, MouseButton)>, _release_data: Option<(Vec2, MouseButton)>, _key_press: Option<KeyCode>, _text_input: &str, _ctx: &mut Context, app_events: &mut Vec<AppEvent>) {
+        if let Some(data) = press_data {
+            if self.back_button.is_clicked(Some(data.0)) {
+                app_events.push(AppEvent::OpenMainMenu);
+            }
+
+            let clicked_option = self.option_buttons.iter().position(|button| button.is_clicked(Some(data.0)));
+
+            if let Some(option) = clicked_option {
+                self.change_option(option);
+                self.update_button_texts();
+
+                app_events.push(AppEvent::UpdateSettings(self.settings.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use std::{collections::BTreeMap, io::{self, BufRead, BufReader, Read, Write}, net::{TcpListener, TcpStream}, sync::mpsc::{self, Receiver, TryRecvError}, thread, time::Duration};
+
+use serde::Deserialize;
+use serde_json::json;
+use tungstenite::{handshake::derive_accept_key, protocol::Role, Error as WsError, Message, WebSocket};
+
+use server_game::ServerGame;
+
+pub mod server_game;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8000";
+
+// a slow client must not hold up the requests behind it
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_BODY_SIZE: usize = 64 * 1024;
+// how long a stream waits on its client before sending the moves played meanwhile
+const STREAM_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// the parts of an http/1.1 request the routes need, every connection answers one request
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Request {
+    fn read(stream: &TcpStream) -> Result<Self, String> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+
+        reader.read_line(&mut line).map_err(|io_error| io_error.to_string())?;
+
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+            return Err("invalid request line".into());
+        };
+
+        let mut request = Self {
+            method: method.into(),
+            path: path.into(),
+            headers: Vec::new(),
+            body: String::new(),
+        };
+
+        loop {
+            line.clear();
+            reader.read_line(&mut line).map_err(|io_error| io_error.to_string())?;
+
+            let Some((name, value)) = line.split_once(':') else {
+                break;
+            };
+
+            request.headers.push((name.trim().into(), value.trim().into()));
+        }
+
+        let length: usize = request.get_header("Content-Length").and_then(|length| length.parse().ok()).unwrap_or(0);
+
+        if length > MAX_BODY_SIZE {
+            return Err("request body too large".into());
+        }
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).map_err(|io_error| io_error.to_string())?;
+        request.body = String::from_utf8(body).map_err(|utf8_error| utf8_error.to_string())?;
+
+        Ok(request)
+    }
+
+    fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct NewGameRequest {
+    fen: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MoveRequest {
+    #[serde(rename = "move")]
+    uci: String,
+}
+
+enum Reply {
+    Json(u16, serde_json::Value),
+    Text(String),
+    Stream(u64),
+}
+
+fn error(status: u16, message: impl Into<String>) -> Reply {
+    Reply::Json(status, json!({ "error": message.into() }))
+}
+
+// serves games over http, one request at a time
+pub struct GameServer {
+    games: BTreeMap<u64, ServerGame>,
+    next_id: u64,
+}
+
+impl GameServer {
+    pub fn new() -> Self {
+        Self {
+            games: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    fn get_game(&mut self, id: &str) -> Result<&mut ServerGame, Reply> {
+        id.parse::<u64>().ok()
+            .and_then(|id| self.games.get_mut(&id))
+            .ok_or(error(404, format!("no game '{}'", id)))
+    }
+
+    fn route(&mut self, method: &str, path: &str, body: &str) -> Result<Reply, Reply> {
+        let segments: Vec<&str> = path.split('?').next().unwrap_or("").split('/').filter(|segment| !segment.is_empty()).collect();
+
+        match (method, segments.as_slice()) {
+            ("GET", ["games"]) => {
+                let games: Vec<_> = self.games.values().map(|game| game.get_state()).collect();
+
+                Ok(Reply::Json(200, json!(games)))
+            },
+            ("POST", ["games"]) => {
+                let request: NewGameRequest = if body.trim().is_empty() {
+                    NewGameRequest::default()
+                } else {
+                    serde_json::from_str(body).map_err(|parse_error| error(400, parse_error.to_string()))?
+                };
+
+                let game = ServerGame::new(self.next_id, request.fen).map_err(|fen_error| error(400, fen_error))?;
+                let state = game.get_state();
+
+                self.games.insert(self.next_id, game);
+                self.next_id += 1;
+
+                Ok(Reply::Json(201, json!(state)))
+            },
+            ("GET", ["games", id]) => Ok(Reply::Json(200, json!(self.get_game(id)?.get_state()))),
+            ("GET", ["games", id, "moves"]) => Ok(Reply::Json(200, json!(self.get_game(id)?.get_legal_moves()))),
+            ("POST", ["games", id, "moves"]) => {
+                let request: MoveRequest = serde_json::from_str(body).map_err(|parse_error| error(400, parse_error.to_string()))?;
+                let game = self.get_game(id)?;
+
+                game.play(&request.uci).map_err(|move_error| error(400, move_error))?;
+
+                Ok(Reply::Json(200, json!(game.get_state())))
+            },
+            ("GET", ["games", id, "fen"]) => Ok(Reply::Text(self.get_game(id)?.get_fen())),
+            ("GET", ["games", id, "pgn"]) => Ok(Reply::Text(self.get_game(id)?.get_pgn())),
+            ("GET", ["games", id, "stream"]) => Ok(Reply::Stream(self.get_game(id)?.id)),
+            (_, ["games"] | ["games", _] | ["games", _, "moves" | "fen" | "pgn" | "stream"]) => Err(error(405, "method not allowed")),
+            _ => Err(error(404, format!("no route for '{}'", path))),
+        }
+    }
+
+    fn open_stream(&mut self, mut stream: TcpStream, request: &Request, id: u64) -> Result<(), String> {
+        let Some(key) = request.get_header("Sec-WebSocket-Key") else {
+            return Self::respond(&stream, error(400, "expected a websocket upgrade"));
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            derive_accept_key(key.as_bytes()),
+        ).map_err(|io_error| io_error.to_string())?;
+
+        stream.set_read_timeout(Some(STREAM_POLL_INTERVAL)).map_err(|io_error| io_error.to_string())?;
+
+        let (sender, receiver) = mpsc::channel::<String>();
+
+        if let Some(game) = self.games.get_mut(&id) {
+            game.subscribe(sender);
+        }
+
+        thread::spawn(move || Self::run_stream(stream, receiver));
+
+        Ok(())
+    }
+
+    // reads between the sends so pings and closes are answered, the thread ends as soon as
+    // the client is gone and dropping the receiver unsubscribes it
+    fn run_stream(stream: TcpStream, receiver: Receiver<String>) {
+        let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+        loop {
+            match socket.read() {
+                // clients have nothing to say, replies to pings and closes are queued by the socket
+                Ok(_) => (),
+                Err(WsError::Io(io_error)) if matches!(io_error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => (),
+                Err(_) => return,
+            }
+
+            loop {
+                match receiver.try_recv() {
+                    Ok(message) => {
+                        if socket.send(Message::Text(message)).is_err() {
+                            return;
+                        }
+                    },
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        let _ = socket.close(None);
+
+                        return;
+                    },
+                }
+            }
+        }
+    }
+
+    fn respond(mut stream: &TcpStream, reply: Reply) -> Result<(), String> {
+        let (status, body, content_type) = match reply {
+            Reply::Json(status, value) => (status, value.to_string(), "application/json"),
+            Reply::Text(text) => (200, text, "text/plain; charset=utf-8"),
+            Reply::Stream(_) => (400, String::new(), "text/plain; charset=utf-8"),
+        };
+
+        let reason = match status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "",
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, reason, content_type, body.len(), body,
+        ).and_then(|_| stream.flush()).map_err(|io_error| io_error.to_string())
+    }
+
+    pub fn handle(&mut self, stream: TcpStream) -> Result<(), String> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+            .map_err(|io_error| io_error.to_string())?;
+
+        let request = match Request::read(&stream) {
+            Ok(request) => request,
+            Err(read_error) => return Self::respond(&stream, error(400, read_error)),
+        };
+
+        let reply = self.route(&request.method, &request.path, &request.body).unwrap_or_else(|reply| reply);
+
+        match reply {
+            Reply::Stream(id) => self.open_stream(stream, &request, id),
+            reply => Self::respond(&stream, reply),
+        }.map_err(|handle_error| format!("{} {}: {}", request.method, request.path, handle_error))
+    }
+}
+
+pub fn run(address: &str) -> Result<(), String> {
+    let listener = TcpListener::bind(address).map_err(|bind_error| format!("could not listen on {}: {}", address, bind_error))?;
+    let mut game_server = GameServer::new();
+
+    println!("Game server listening on http://{}", address);
+
+    for stream in listener.incoming() {
+        let result = stream.map_err(|accept_error| accept_error.to_string()).and_then(|stream| game_server.handle(stream));
+
+        if let Err(handle_error) = result {
+            println!("Could not answer a request: {}", handle_error);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::{Read, Write}, net::{SocketAddr, TcpListener, TcpStream}, thread, time::Duration};
+
+    use serde_json::Value;
+    use tungstenite::{Error as WsError, Message};
+
+    use super::*;
+
+    fn get_json(reply: Result<Reply, Reply>) -> (u16, Value) {
+        match reply {
+            Ok(Reply::Json(status, value)) | Err(Reply::Json(status, value)) => (status, value),
+            _ => panic!("expected a json reply"),
+        }
+    }
+
+    fn get_text(reply: Result<Reply, Reply>) -> String {
+        match reply {
+            Ok(Reply::Text(text)) => text,
+            _ => panic!("expected a text reply"),
+        }
+    }
+
+    #[test]
+    fn routes_a_game() {
+        let mut server = GameServer::new();
+
+        let (status, state) = get_json(server.route("POST", "/games", ""));
+        assert_eq!(status, 201);
+        assert_eq!(state["id"], 1);
+
+        let (status, moves) = get_json(server.route("GET", "/games/1/moves", ""));
+        assert_eq!(status, 200);
+        assert_eq!(moves.as_array().unwrap().len(), 20);
+        assert!(moves.as_array().unwrap().iter().any(|legal_move| legal_move["uci"] == "e2e4" && legal_move["san"] == "e4"));
+
+        let (status, state) = get_json(server.route("POST", "/games/1/moves", r#"{"move": "e2e4"}"#));
+        assert_eq!(status, 200);
+        assert_eq!(state["moves"], serde_json::json!(["e2e4"]));
+        assert_eq!(state["turn"], "Black");
+
+        let (status, _) = get_json(server.route("POST", "/games/1/moves", r#"{"move": "e2e4"}"#));
+        assert_eq!(status, 400);
+
+        let (status, _) = get_json(server.route("GET", "/games/2", ""));
+        assert_eq!(status, 404);
+
+        let (status, _) = get_json(server.route("GET", "/games/1/foo", ""));
+        assert_eq!(status, 404);
+
+        let (status, _) = get_json(server.route("DELETE", "/games/1", ""));
+        assert_eq!(status, 405);
+
+        assert_eq!(get_text(server.route("GET", "/games/1/fen", "")), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert!(get_text(server.route("GET", "/games/1/pgn", "")).contains("1. e4 *"));
+    }
+
+    fn start_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut server = GameServer::new();
+
+            for stream in listener.incoming() {
+                server.handle(stream.unwrap()).unwrap();
+            }
+        });
+
+        address
+    }
+
+    fn send_request(address: SocketAddr, method: &str, path: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, path, body.len(), body).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        response
+    }
+
+    fn read_event(socket: &mut WebSocket<TcpStream>) -> Value {
+        match socket.read().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            message => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn streams_moves_over_a_websocket() {
+        let address = start_server();
+
+        assert!(send_request(address, "POST", "/games", "").starts_with("HTTP/1.1 201"));
+
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let (mut socket, _) = tungstenite::client(format!("ws://{}/games/1/stream", address), stream).unwrap();
+
+        let event = read_event(&mut socket);
+        assert_eq!(event["type"], "state");
+        assert_eq!(event["moves"], serde_json::json!([]));
+
+        // the server reads its side of the socket too
+        socket.send(Message::Ping(b"ping".to_vec())).unwrap();
+        assert_eq!(socket.read().unwrap(), Message::Pong(b"ping".to_vec()));
+
+        assert!(send_request(address, "POST", "/games/1/moves", r#"{"move": "g1f3"}"#).starts_with("HTTP/1.1 200"));
+
+        let event = read_event(&mut socket);
+        assert_eq!(event["type"], "move");
+        assert_eq!(event["uci"], "g1f3");
+        assert_eq!(event["san"], "Nf3");
+
+        socket.close(None).unwrap();
+
+        loop {
+            match socket.read() {
+                Ok(_) => (),
+                Err(WsError::ConnectionClosed) => break,
+                Err(ws_error) => panic!("the close was not answered: {}", ws_error),
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::{collections::HashMap, sync::mpsc::Sender};
+
+use serde::Serialize;
+
+use crate::game::{board::Board, fen, notation, piece::{PieceType, Player}};
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    Repetition,
+    FiftyMoveRule,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LegalMove {
+    pub uci: String,
+    pub san: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GameState {
+    pub id: u64,
+    pub fen: String,
+    pub turn: Player,
+    pub is_check: bool,
+    pub status: GameStatus,
+    pub winner: Option<Player>,
+    pub moves: Vec<String>,
+    pub san: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    State(GameState),
+    Move { ply: usize, uci: String, san: String, fen: String },
+    End { status: GameStatus, winner: Option<Player> },
+}
+
+// a game without a window, driven by requests to the server
+pub struct ServerGame {
+    pub id: u64,
+    pub start_fen: Option<String>,
+    pub start_player: Player,
+    pub start_fullmove: u32,
+    pub board: Board,
+    pub current_player: Player,
+    pub fifty_move_rule: u8,
+    pub snapshots: HashMap<String, u8>,
+    pub moves: Vec<LegalMove>,
+    pub status: GameStatus,
+    pub winner: Option<Player>,
+    subscribers: Vec<Sender<String>>,
+}
+
+impl ServerGame {
+    pub fn new(id: u64, start_fen: Option<String>) -> Result<Self, String> {
+        let start = fen::parse_fen(start_fen.as_deref().unwrap_or(fen::STARTING_FEN))?;
+
+        let mut game = Self {
+            id,
+            start_fen,
+            start_player: start.current_player,
+            start_fullmove: start.fullmove_number,
+            board: start.board,
+            current_player: start.current_player,
+            fifty_move_rule: start.fifty_move_rule,
+            snapshots: HashMap::new(),
+            moves: Vec::new(),
+            status: GameStatus::Ongoing,
+            winner: None,
+            subscribers: Vec::new(),
+        };
+
+        game.record_snapshot();
+        game.update_status();
+
+        Ok(game)
+    }
+
+    pub fn get_fen(&self) -> String {
+        let plies = self.moves.len() as u32 + if self.start_player == Player::Black { 1 } else { 0 };
+
+        fen::get_fen(&self.board, self.current_player, self.fifty_move_rule, self.start_fullmove + plies / 2)
+    }
+
+    pub fn get_state(&self) -> GameState {
+        GameState {
+            id: self.id,
+            fen: self.get_fen(),
+            turn: self.current_player,
+            is_check: self.board.get_check(self.current_player),
+            status: self.status,
+            winner: self.winner,
+            moves: self.moves.iter().map(|played_move| played_move.uci.clone()).collect(),
+            san: self.moves.iter().map(|played_move| played_move.san.clone()).collect(),
+        }
+    }
+
+    pub fn get_legal_moves(&self) -> Vec<LegalMove> {
+        if self.status != GameStatus::Ongoing {
+            return Vec::new();
+        }
+
+        let mut legal_moves = Vec::new();
+
+        for piece_move in self.board.get_legal_moves(self.current_player) {
+            let promotions = if piece_move.is_pawn_promotion_move() {
+                vec![Some(PieceType::Queen), Some(PieceType::Rook), Some(PieceType::Bishop), Some(PieceType::Knight)]
+            } else {
+                vec![None]
+            };
+
+            for promotion in promotions {
+                legal_moves.push(LegalMove {
+                    uci: notation::get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion),
+                    san: notation::get_san(&self.board, piece_move.as_ref(), promotion),
+                });
+            }
+        }
+
+        legal_moves
+    }
+
+    fn record_snapshot(&mut self) -> u8 {
+        let count = self.snapshots.entry(self.board.get_snapshot()).or_insert(0);
+        *count += 1;
+
+        *count
+    }
+
+    fn update_status(&mut self) {
+        let is_checked = self.board.get_check(self.current_player);
+        let pieces = self.board.get_total_pieces();
+        let repetitions = self.snapshots.get(&self.board.get_snapshot()).copied().unwrap_or(0);
+
+        if self.board.get_legal_moves(self.current_player).is_empty() {
+            self.status = if is_checked { GameStatus::Checkmate } else { GameStatus::Stalemate };
+
+            if is_checked {
+                self.winner = Some(self.current_player.get_opponent());
+            }
+        }
+        else if !pieces.has_mating_material(Player::White) && !pieces.has_mating_material(Player::Black) {
+            self.status = GameStatus::InsufficientMaterial;
+        }
+        else if repetitions >= 3 {
+            self.status = GameStatus::Repetition;
+        }
+        else if self.fifty_move_rule >= 100 {
+            self.status = GameStatus::FiftyMoveRule;
+        }
+    }
+
+    pub fn play(&mut self, uci: &str) -> Result<LegalMove, String> {
+        if self.status != GameStatus::Ongoing {
+            return Err("the game is over".into());
+        }
+
+        let (from_pos, to_pos, promotion) = notation::parse_uci(uci).ok_or(format!("invalid move '{}'", uci))?;
+        let piece_move = self.board.find_move(self.current_player, from_pos, to_pos).ok_or(format!("illegal move '{}'", uci))?;
+
+        let promotion = if piece_move.is_pawn_promotion_move() {
+            Some(promotion.unwrap_or(PieceType::Queen))
+        } else {
+            None
+        };
+
+        let is_capture = !piece_move.is_castle_move() && self.board[piece_move.get_to_pos()].is_some();
+        let is_pawn_move = self.board[piece_move.get_from_pos()].as_ref().is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn);
+
+        let played_move = LegalMove {
+            uci: notation::get_uci(piece_move.get_from_pos(), piece_move.get_to_pos(), promotion),
+            san: notation::get_san(&self.board, piece_move.as_ref(), promotion),
+        };
+
+        piece_move.execute(&mut self.board);
+
+        if let Some(piece_type) = promotion {
+            self.board.promote(piece_move.get_to_pos(), piece_type);
+        }
+
+        self.fifty_move_rule = if is_capture || is_pawn_move { 0 } else { self.fifty_move_rule + 1 };
+        self.current_player = self.current_player.get_opponent();
+        self.moves.push(played_move.clone());
+
+        self.record_snapshot();
+        self.update_status();
+
+        self.broadcast(&StreamEvent::Move {
+            ply: self.moves.len() - 1,
+            uci: played_move.uci.clone(),
+            san: played_move.san.clone(),
+            fen: self.get_fen(),
+        });
+
+        if self.status != GameStatus::Ongoing {
+            self.broadcast(&StreamEvent::End { status: self.status, winner: self.winner });
+        }
+
+        Ok(played_move)
+    }
+
+    pub fn get_result(&self) -> &str {
+        match (self.status, self.winner) {
+            (GameStatus::Ongoing, _) => "*",
+            (_, Some(Player::White)) => "1-0",
+            (_, Some(Player::Black)) => "0-1",
+            (_, None) => "1/2-1/2",
+        }
+    }
+
+    pub fn get_pgn(&self) -> String {
+        let mut pgn = format!("[Event \"Server game {}\"]\n[Site \"rust_games\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"{}\"]\n", self.id, self.get_result());
+
+        if let Some(start_fen) = &self.start_fen {
+            pgn += &format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", start_fen);
+        }
+
+        pgn + "\n" + &self.get_movetext(self.get_result()) + "\n"
+    }
+
+    // the numbered moves followed by the result
+    pub fn get_movetext(&self, result: &str) -> String {
+        let mut tokens = Vec::new();
+        let first_ply = if self.start_player == Player::Black { 1 } else { 0 };
+
+        for (i, played_move) in self.moves.iter().enumerate() {
+            let ply = first_ply + i;
+            let move_number = self.start_fullmove as usize + ply / 2;
+
+            if ply % 2 == 0 {
+                tokens.push(format!("{}.", move_number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", move_number));
+            }
+
+            tokens.push(played_move.san.clone());
+        }
+
+        tokens.push(result.into());
+
+        tokens.join(" ")
+    }
+
+    // new subscribers get the current state first
+    pub fn subscribe(&mut self, sender: Sender<String>) {
+        if sender.send(serde_json::to_string(&StreamEvent::State(self.get_state())).unwrap()).is_ok() {
+            self.subscribers.push(sender);
+        }
+    }
+
+    fn broadcast(&mut self, event: &StreamEvent) {
+        let message = serde_json::to_string(event).unwrap();
+
+        self.subscribers.retain(|subscriber| subscriber.send(message.clone()).is_ok());
+    }
+}
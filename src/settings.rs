@@ -0,0 +1,197 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{assets::DEFAULT_THEME, engine::{search::get_max_threads, transposition::{DEFAULT_HASH_SIZE, MAX_HASH_SIZE}}};
+
+pub const TIME_CONTROLS: [Option<TimeControl>; 7] = [
+    None,
+    Some(TimeControl { minutes: 1, increment: 0 }),
+    Some(TimeControl { minutes: 3, increment: 2 }),
+    Some(TimeControl { minutes: 5, increment: 0 }),
+    Some(TimeControl { minutes: 10, increment: 0 }),
+    Some(TimeControl { minutes: 15, increment: 10 }),
+    Some(TimeControl { minutes: 30, increment: 0 }),
+];
+
+const VOLUMES: [f32; 4] = [0.25, 0.5, 0.75, 1.];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AnimationSpeed {
+    Off,
+    Fast,
+    Normal,
+    Slow,
+}
+
+impl AnimationSpeed {
+    pub fn get_duration(&self) -> f32 {
+        match self {
+            AnimationSpeed::Off => 0.,
+            AnimationSpeed::Fast => 0.1,
+            AnimationSpeed::Normal => 0.2,
+            AnimationSpeed::Slow => 0.4,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AnimationSpeed::Off => AnimationSpeed::Fast,
+            AnimationSpeed::Fast => AnimationSpeed::Normal,
+            AnimationSpeed::Normal => AnimationSpeed::Slow,
+            AnimationSpeed::Slow => AnimationSpeed::Off,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TimeControl {
+    pub minutes: u32,
+    pub increment: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: String,
+    pub flipped: bool,
+    pub animation_speed: AnimationSpeed,
+    pub muted: bool,
+    pub volume: f32,
+    pub auto_queen: bool,
+    pub confirm_moves: bool,
+    // shade the squares the opponent attacks
+    pub show_threats: bool,
+    // type moves instead of only clicking them
+    pub keyboard_moves: bool,
+    pub time_control: Option<TimeControl>,
+    // a polyglot .bin book for the built-in engine and analysis
+    pub opening_book: Option<String>,
+    // a directory of syzygy tables for the built-in engine and analysis
+    pub tablebase_path: Option<String>,
+    // the transposition table of the built-in engine in megabytes
+    pub hash_size: usize,
+    // search threads of the engines and analysis
+    pub threads: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: DEFAULT_THEME.into(),
+            flipped: false,
+            animation_speed: AnimationSpeed::Normal,
+            muted: false,
+            volume: 1.,
+            auto_queen: false,
+            confirm_moves: false,
+            show_threats: false,
+            keyboard_moves: false,
+            time_control: None,
+            opening_book: None,
+            tablebase_path: None,
+            hash_size: DEFAULT_HASH_SIZE,
+            threads: 1,
+        }
+    }
+}
+
+impl Settings {
+    pub fn get_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rust_games").join("settings.toml"))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::get_path() else {
+            return Self::default();
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::default(),
+        };
+
+        let mut settings: Self = toml::from_str(&contents).unwrap_or_else(|error| {
+            println!("Invalid settings file '{}': {}", path.display(), error);
+
+            Self::default()
+        });
+
+        // the file may come from a machine with more cores
+        settings.threads = settings.threads.clamp(1, get_max_threads());
+
+        settings
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::get_path() else {
+            return;
+        };
+
+        let result = toml::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+
+                fs::write(&path, contents).map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            println!("Could not save settings to '{}': {}", path.display(), error);
+        }
+    }
+
+    pub fn next_volume(&mut self) {
+        if self.muted {
+            self.muted = false;
+            self.volume = VOLUMES[0];
+            return;
+        }
+
+        match VOLUMES.iter().position(|volume| *volume >= self.volume) {
+            Some(index) if index + 1 < VOLUMES.len() => self.volume = VOLUMES[index + 1],
+            _ => self.muted = true,
+        }
+    }
+
+    pub fn next_time_control(&mut self) {
+        let index = TIME_CONTROLS.iter().position(|time_control| *time_control == self.time_control).unwrap_or(0);
+
+        self.time_control = TIME_CONTROLS[(index + 1) % TIME_CONTROLS.len()];
+    }
+
+    // doubles up to the cores of the machine
+    pub fn next_threads(&mut self) {
+        self.threads = if self.threads >= get_max_threads() {
+            1
+        } else {
+            (self.threads * 2).min(get_max_threads())
+        };
+    }
+
+    // doubles up to the largest table and starts again from the default
+    pub fn next_hash_size(&mut self) {
+        self.hash_size = if self.hash_size >= MAX_HASH_SIZE {
+            DEFAULT_HASH_SIZE
+        } else {
+            (self.hash_size * 2).clamp(1, MAX_HASH_SIZE)
+        };
+    }
+
+    pub fn get_volume_name(&self) -> String {
+        if self.muted {
+            return "Off".into();
+        }
+
+        format!("{}%", (self.volume * 100.).round())
+    }
+
+    pub fn get_time_control_name(&self) -> String {
+        match self.time_control {
+            Some(time_control) => format!("{}+{}", time_control.minutes, time_control.increment),
+            None => "Untimed".into(),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use ggez::audio::{SoundSource, Source};
+
+use crate::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SoundEffect {
+    Move,
+    Capture,
+    Castle,
+    Check,
+    Promotion,
+    LowTime,
+    GameEnd,
+}
+
+const SOUND_FILES: [(SoundEffect, &str); 7] = [
+    (SoundEffect::Move, "/sounds/move.wav"),
+    (SoundEffect::Capture, "/sounds/capture.wav"),
+    (SoundEffect::Castle, "/sounds/castle.wav"),
+    (SoundEffect::Check, "/sounds/check.wav"),
+    (SoundEffect::Promotion, "/sounds/promotion.wav"),
+    (SoundEffect::LowTime, "/sounds/lowtime.wav"),
+    (SoundEffect::GameEnd, "/sounds/gameend.wav"),
+];
+
+pub struct Sounds {
+    pub sources: HashMap<SoundEffect, Source>,
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Sounds {
+    pub fn load_all(ctx: &mut Context) -> Self {
+        let mut sources = HashMap::new();
+
+        for (effect, path) in SOUND_FILES {
+            match Source::new(ctx, path) {
+                Ok(source) => {
+                    sources.insert(effect, source);
+                },
+                Err(error) => println!("Could not load sound '{}': {}", path, error),
+            }
+        }
+
+        Self {
+            sources,
+            volume: 1.,
+            muted: false,
+        }
+    }
+
+    pub fn play(&mut self, ctx: &mut Context, effect: SoundEffect) {
+        if self.muted {
+            return;
+        }
+
+        if let Some(source) = self.sources.get_mut(&effect) {
+            source.set_volume(self.volume);
+
+            if let Err(error) = source.play_detached(ctx) {
+                println!("Could not play sound {:?}: {}", effect, error);
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use ggez::{glam::Vec2, graphics::{Canvas, Drawable, Mesh, Rect, Text}, Context};
+use ggez::{glam::Vec2, graphics::{Canvas, Color, DrawMode, DrawParam, Drawable, Mesh, Rect, Text}, input::keyboard::KeyCode, Context};
 
 
 pub struct Button {
@@ -41,4 +41,87 @@ impl Button {
 
         false
     }
-}
\ No newline at end of file
+}
+
+pub struct TextField {
+    pub text: String,
+    pub position: Vec2,
+    pub width: f32,
+    pub is_focused: bool,
+}
+
+impl TextField {
+    pub fn new(text: &str, position: Vec2, width: f32) -> Self {
+        Self {
+            text: text.into(),
+            position,
+            width,
+            is_focused: false,
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas, ctx: &mut Context) {
+        canvas.draw(&Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., self.width, 20.),
+            Color::WHITE
+        ).unwrap(), self.position);
+
+        canvas.draw(&Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(1.),
+            Rect::new(0., 0., self.width, 20.),
+            if self.is_focused { Color::BLUE } else { Color::BLACK }
+        ).unwrap(), self.position);
+
+        // show the end of the text when it doesn't fit
+        let mut visible: &str = &self.text;
+        let mut text = Text::new(visible);
+        text.set_scale(12.);
+
+        while text.dimensions(ctx).unwrap().w > self.width - 8. && !visible.is_empty() {
+            let mut chars = visible.chars();
+            chars.next();
+            visible = chars.as_str();
+
+            text = Text::new(visible);
+            text.set_scale(12.);
+        }
+
+        canvas.draw(&text, DrawParam::new().dest([self.position.x + 4., self.position.y + 4.]).color(Color::BLACK));
+    }
+
+    pub fn is_clicked(&self, press_position: Option<Vec2>) -> bool {
+        if let Some(position) = press_position {
+            return position.x > self.position.x && position.x < self.position.x + self.width
+            && position.y > self.position.y && position.y < self.position.y + 20.;
+        }
+
+        false
+    }
+
+    // returns true when the text changed
+    pub fn update(&mut self, press_position: Option<Vec2>, text_input: &str, key_press: Option<KeyCode>) -> bool {
+        if press_position.is_some() {
+            self.is_focused = self.is_clicked(press_position);
+        }
+
+        if !self.is_focused {
+            return false;
+        }
+
+        let mut changed = false;
+
+        for character in text_input.chars().filter(|character| !character.is_control()) {
+            self.text.push(character);
+            changed = true;
+        }
+
+        if key_press == Some(KeyCode::Back) {
+            changed = self.text.pop().is_some() || changed;
+        }
+
+        changed
+    }
+}